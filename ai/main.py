@@ -1,9 +1,8 @@
 import cv2 as cv
 import mediapipe as mp
 import numpy as np
-import pyautogui
-import threading
-import queue
+import os
+import socket
 import time
 
 # MediaPipe Hand Landmarker setup
@@ -15,104 +14,26 @@ HandLandmarkerOptions = mp.tasks.vision.HandLandmarkerOptions
 HandLandmarkerResult = mp.tasks.vision.HandLandmarkerResult
 VisionRunningMode = mp.tasks.vision.RunningMode
 
-# Get screen dimensions
-screenWidth, screenHeight = pyautogui.size()
+# The game filters the raw aim itself, see src/input.rs
+GAME_ADDRESS = ('127.0.0.1', 7878)
+game_socket = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
 
-# Create thread-safe queues
-mouse_queue = queue.Queue()
-click_queue = queue.Queue()
-key_queue = queue.Queue()
+# Set TRACKER_RECORD to a path to also save the stream, the game can play it
+# back without a webcam with SYNTHETIC_TRACKER=<path> (see src/synthetic.rs)
+record_path = os.environ.get('TRACKER_RECORD')
+record_file = open(record_path, 'w') if record_path else None
+record_start = time.monotonic()
 
-class SmoothMouseController:
-    def __init__(self, smoothing=0.5, speed_factor=2.0):
-        """
-        Initialize smooth mouse controller
-        :param smoothing: Smoothing factor (0-1, lower = smoother but slower)
-        :param speed_factor: Multiplier for cursor speed
-        """
-        self.current_x, self.current_y = pyautogui.position()
-        self.smoothing = smoothing
-        self.speed_factor = speed_factor
-        self.last_click_time = 0
-        self.click_cooldown = 0.5  # Prevent rapid successive clicks
-        self.last_key_time = 0
-        self.key_cooldown = 0.5  # Prevent rapid key presses
-
-    def update_position(self, target_x, target_y):
-        """
-        Smoothly move the cursor towards the target position
-        """
-        # Invert Y-axis to match screen coordinates
-        target_y = screenHeight - target_y
-
-        # Calculate smooth interpolation
-        new_x = (1 - self.smoothing) * target_x + self.smoothing * self.current_x
-        new_y = (1 - self.smoothing) * target_y + self.smoothing * self.current_y
-
-        # Apply speed factor
-        dx = (new_x - self.current_x) * self.speed_factor
-        dy = (new_y - self.current_y) * self.speed_factor
-
-        # Update current position
-        self.current_x += dx
-        self.current_y += dy
-
-        # Ensure cursor stays within screen bounds
-        self.current_x = max(0, min(self.current_x, screenWidth))
-        self.current_y = max(0, min(self.current_y, screenHeight))
-
-        # Move the cursor
-        pyautogui.moveTo(self.current_x, self.current_y)
-
-    def try_click(self):
-        """
-        Perform a click with a cooldown to prevent spam
-        """
-        current_time = time.time()
-        if current_time - self.last_click_time > self.click_cooldown:
-            pyautogui.click()
-            self.last_click_time = current_time
-
-    def try_key_press(self, key):
-        """
-        Perform a key press with a cooldown to prevent spam
-        """
-        current_time = time.time()
-        if current_time - self.last_key_time > self.key_cooldown:
-            pyautogui.press(key)
-            self.last_key_time = current_time
-
-# Initialize smooth mouse controller
-mouse_controller = SmoothMouseController()
-
-def mouse_control_thread():
+def send_to_game(message):
     """
-    Separate thread to handle smooth mouse movement, clicking, and key presses
+    Send one message to the game, dropping it if the game isn't running
     """
-    while True:
-        try:
-            # Handle mouse movement
-            if not mouse_queue.empty():
-                landmarks = mouse_queue.get()
-                mouse_controller.update_position(
-                    landmarks['mouse_x'], 
-                    landmarks['mouse_y']
-                )
-            
-            # Handle clicking
-            if not click_queue.empty():
-                click_queue.get()
-                mouse_controller.try_click()
-            
-            # Handle key presses
-            if not key_queue.empty():
-                key = key_queue.get()
-                mouse_controller.try_key_press(key)
-            
-            time.sleep(0.01)  # Prevent tight looping
-        except Exception as e:
-            print(f"Mouse control thread error: {e}")
-            break
+    if record_file:
+        record_file.write(f"{time.monotonic() - record_start:.3f} {message}\n")
+    try:
+        game_socket.sendto(message.encode(), GAME_ADDRESS)
+    except OSError:
+        pass
 
 def is_hand_closed(landmarks):
     """
@@ -139,7 +60,7 @@ def is_hand_closed(landmarks):
 
 def callback_fn(result, output_image, timestamp_ms):
     """
-    Callback function that handles mouse movement, clicking, and gesture detection
+    Callback function that streams the aim and raw gesture states to the game
     """
     if result.hand_landmarks and result.handedness:
         for idx, landmarks in enumerate(result.hand_landmarks):
@@ -147,29 +68,16 @@ def callback_fn(result, output_image, timestamp_ms):
             # MediaPipe returns handedness with probability
             handedness = result.handedness[idx][0]
             
-            # Only process right hand for mouse and click
+            # The right hand aims and pinches to fire
             if handedness.category_name.lower() == 'right':
                 # Cursor movement (using index finger)
                 index_finger = landmarks[8]
                 
-                # Map normalized coordinates to screen coordinates
+                # Send the raw fingertip, normalized with y down
                 # Flip x-coordinate to match natural hand movement
-                mouse_x = int((1 - index_finger.x) * screenWidth)
-                mouse_y = int((1 - index_finger.y) * screenHeight)
+                send_to_game(f"aim {1 - index_finger.x:.4f} {index_finger.y:.4f}")
                 
-                # Put movement coordinates in queue
-                try:
-                    # Clear any existing movement items
-                    while not mouse_queue.empty():
-                        mouse_queue.get()
-                    mouse_queue.put({
-                        'mouse_x': mouse_x, 
-                        'mouse_y': mouse_y
-                    })
-                except Exception as e:
-                    print(f"Error in movement queue: {e}")
-                
-                # Pinch-to-click (thumb tip and middle finger tip)
+                # Pinch to fire (thumb tip and middle finger tip)
                 thumb_tip = landmarks[4]
                 middle_finger_tip = landmarks[11]
                 
@@ -179,28 +87,12 @@ def callback_fn(result, output_image, timestamp_ms):
                     (thumb_tip.y - middle_finger_tip.y)**2
                 )
                 
-                # If tips are close, trigger a click
-                if distance < 0.05:
-                    try:
-                        click_queue.put(True)
-                    except Exception as e:
-                        print(f"Error in click queue: {e}")
+                # The game decides when a pinch counts as a shot
+                send_to_game(f"pinch {distance:.4f}")
             
-            # Left hand gesture detection remains the same
             if handedness.category_name.lower() == 'left':
-                # Detect open or closed hand
-                if is_hand_closed(landmarks):
-                    # Closed hand - press '2'
-                    try:
-                        key_queue.put('2')
-                    except Exception as e:
-                        print(f"Error in key queue: {e}")
-                else:
-                    # Open hand - press '1'
-                    try:
-                        key_queue.put('1')
-                    except Exception as e:
-                        print(f"Error in key queue: {e}")
+                # The game switches color once the fist has settled
+                send_to_game(f"fist {int(is_hand_closed(landmarks))}")
 
 # Path to the pre-trained model
 model_path = './hand_landmarker.task'
@@ -216,10 +108,6 @@ options = HandLandmarkerOptions(
     result_callback=callback_fn
 )
 
-# Start the mouse control thread
-mouse_thread = threading.Thread(target=mouse_control_thread, daemon=True)
-mouse_thread.start()
-
 # OpenCV VideoCapture setup
 cap = cv.VideoCapture(0)
 if not cap.isOpened():
@@ -0,0 +1,136 @@
+use crate::coop::SecondPlayer;
+use crate::daily::{DailyRun, Modifier};
+use crate::enemy::Enemy;
+use crate::globals::{GameState, Kulay};
+use crate::hud::world_to_viewport;
+use crate::input::Pointer;
+use crate::player::{CamMarker, Shooter};
+use crate::settings::Settings;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+// how much of the cursor's motion is kept inside the slow-down zone at full strength
+const SLOWDOWN: f32 = 0.6;
+// per second, how quickly the crosshair catches back up with the cursor after a slow-down
+const RECENTER_RATE: f32 = 8.;
+
+pub struct AimPlugin;
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Aim::default())
+            .insert_resource(AimAssistUsed(false))
+            .add_systems(OnEnter(GameState::InGame), reset_assist_used_system)
+            // runs before Update so the crosshair and the shot see the same point
+            .add_systems(PreUpdate, update_aim_system);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Aim {
+    // where the crosshair sits and shots go, in window coordinates
+    pub pos: Option<Vec2>,
+    last_cursor: Option<Vec2>,
+}
+
+// set once aim assist bent a shot or slowed the crosshair in the current run
+#[derive(Resource)]
+pub struct AimAssistUsed(pub bool);
+
+// the aim point with assist layered on top, for whoever fires the shot
+#[derive(SystemParam)]
+pub struct AimAssist<'w> {
+    aim: Res<'w, Aim>,
+    second: Res<'w, SecondPlayer>,
+    settings: Res<'w, Settings>,
+    used: ResMut<'w, AimAssistUsed>,
+}
+
+impl AimAssist<'_> {
+    // snaps onto the closest enemy within the assist radius, matching color first
+    pub fn shot_target(
+        &mut self,
+        shooter: Shooter,
+        cam: &Camera,
+        cam_transform: &GlobalTransform,
+        enemies: impl Iterator<Item = (Vec3, Kulay)>,
+        color: Kulay,
+    ) -> Option<Vec2> {
+        let aim = match shooter {
+            Shooter::One => self.aim.pos?,
+            Shooter::Two => self.second.pos?,
+        };
+        let strength = self.settings.aim_assist;
+        if strength <= 0. {
+            return Some(aim);
+        }
+
+        let target = enemies
+            .filter_map(|(pos, enemy_color)| {
+                let screen = world_to_viewport(cam, cam_transform, pos)?;
+                let distance = screen.distance(aim);
+                (distance <= self.settings.aim_assist_radius).then_some((
+                    enemy_color != color,
+                    distance,
+                    screen,
+                ))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+            .map(|(_, _, screen)| screen);
+        let Some(target) = target else {
+            return Some(aim);
+        };
+        self.used.0 = true;
+        Some(aim.lerp(target, strength))
+    }
+}
+
+fn reset_assist_used_system(mut used: ResMut<AimAssistUsed>) {
+    used.0 = false;
+}
+
+pub fn update_aim_system(
+    mut aim: ResMut<Aim>,
+    pointer: Res<Pointer>,
+    cam: Query<(&GlobalTransform, &Camera), With<CamMarker>>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    settings: Res<Settings>,
+    mut used: ResMut<AimAssistUsed>,
+    daily_run: Res<DailyRun>,
+    win: Query<&Window, With<PrimaryWindow>>,
+    time: Res<Time<Real>>,
+) {
+    let mirrored = daily_run.has(Modifier::MirroredAim);
+    let cursor = match (pointer.pos, win.get_single()) {
+        (Some(pos), Ok(win)) if mirrored => Some(Vec2::new(win.width() - pos.x, pos.y)),
+        (pos, _) => pos,
+    };
+    let (Some(cursor), Some(last_cursor), Some(pos)) = (cursor, aim.last_cursor, aim.pos) else {
+        aim.pos = cursor;
+        aim.last_cursor = cursor;
+        return;
+    };
+
+    let in_zone = settings.aim_assist > 0.
+        && cam.get_single().is_ok_and(|(cam_transform, cam)| {
+            enemies.iter().any(|enemy| {
+                world_to_viewport(cam, cam_transform, enemy.translation())
+                    .is_some_and(|screen| screen.distance(pos) <= settings.aim_assist_radius)
+            })
+        });
+
+    let mut pos = pos;
+    if in_zone {
+        if cursor != last_cursor {
+            used.0 = true;
+        }
+        pos += (cursor - last_cursor) * (1. - SLOWDOWN * settings.aim_assist);
+    } else {
+        // ease back onto the real cursor so the two never drift apart for good
+        pos += cursor - last_cursor;
+        pos = pos.lerp(cursor, 1. - (-RECENTER_RATE * time.delta_seconds()).exp());
+    }
+    aim.pos = Some(pos);
+    aim.last_cursor = Some(cursor);
+}
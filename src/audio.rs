@@ -0,0 +1,415 @@
+use crate::enemy::{Enemy, EnemyState, MovementSpeed};
+use crate::globals::GameState;
+use crate::settings::Settings;
+use bevy::audio::{AudioSinkPlayback, PitchBundle, SpatialScale, Volume};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// oldest sfx gets cut once this many are playing
+const MAX_SFX_VOICES: usize = 16;
+// in game music switches to the intense track at this many enemies on screen
+const INTENSE_ENEMY_COUNT: usize = 5;
+// and back to the calm one once the field thins out below this, so kills
+// around the threshold don't flip the tracks back and forth
+const CALM_ENEMY_COUNT: usize = 3;
+const MUSIC_FADE_SECS: f32 = 2.;
+// positional one-shots stay audible across the whole lane, the hum only
+// gets loud once an enemy is close
+const SFX_SPATIAL_SCALE: f32 = 0.02;
+const HUM_SPATIAL_SCALE: f32 = 0.1;
+const HUM_FREQUENCY: f32 = 110.;
+const HUM_VOLUME: f32 = 0.3;
+// movement speed that plays the hum at its base pitch
+const HUM_BASE_SPEED: f32 = 8.;
+// rodio can't resample at a speed of zero, so anything standing still still hums low
+const HUM_MIN_PITCH: f32 = 0.1;
+
+pub struct AudioManagerPlugin;
+
+impl Plugin for AudioManagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySfx>()
+            .add_event::<PlaySfxAt>()
+            .add_event::<PlayMusic>()
+            .insert_resource(CurrentMusic(None))
+            .add_systems(PreStartup, load_audio_handles)
+            .add_systems(OnEnter(GameState::MainMenu), menu_music_system)
+            .add_systems(OnEnter(GameState::GameOver), gameover_music_system)
+            .add_systems(
+                Update,
+                ingame_music_system.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    play_music_system,
+                    crossfade_music_system,
+                    play_sfx_system,
+                    attach_enemy_hum_system,
+                    enemy_hum_pitch_system,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                apply_bus_volume_system.run_if(resource_changed::<Settings>),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bus {
+    Music,
+    Sfx,
+    Ui,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SfxId {
+    Attack,
+    Damage,
+    PlayerHit,
+    Click,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicId {
+    Zenith,
+    Banger,
+    GameOverSting,
+}
+
+#[derive(Event)]
+pub struct PlaySfx(pub SfxId);
+
+// same as `PlaySfx` but panned and attenuated from a world position
+#[derive(Event)]
+pub struct PlaySfxAt(pub SfxId, pub Vec3);
+
+// crossfades from whatever track is playing
+#[derive(Event)]
+pub struct PlayMusic(pub MusicId);
+
+#[derive(Resource)]
+pub struct CurrentMusic(pub Option<MusicId>);
+
+#[derive(Component)]
+struct MusicFade {
+    level: f32,
+    target: f32,
+}
+
+#[derive(Component)]
+pub struct Voice {
+    pub bus: Bus,
+    pub volume: f32,
+    started: f32,
+}
+
+#[derive(Component)]
+struct EnemyHum;
+
+#[derive(Resource, Default)]
+struct AudioHandles {
+    sfx: HashMap<SfxId, Handle<AudioSource>>,
+    music: HashMap<MusicId, Handle<AudioSource>>,
+    hum: Handle<Pitch>,
+}
+
+impl Bus {
+    pub fn gain(self, settings: &Settings) -> f32 {
+        settings.master_volume
+            * match self {
+                Bus::Music => settings.music_volume,
+                Bus::Sfx => settings.sfx_volume,
+                Bus::Ui => settings.ui_volume,
+            }
+    }
+}
+
+impl SfxId {
+    const ALL: [SfxId; 4] = [SfxId::Attack, SfxId::Damage, SfxId::PlayerHit, SfxId::Click];
+
+    fn path(self) -> &'static str {
+        match self {
+            SfxId::Attack => "attack.ogg",
+            SfxId::Damage => "damage.ogg",
+            SfxId::PlayerHit => "rizz.ogg",
+            // placeholder: there's no ui click sound yet, a quiet shot stands in
+            SfxId::Click => "attack.ogg",
+        }
+    }
+
+    fn bus(self) -> Bus {
+        match self {
+            SfxId::Click => Bus::Ui,
+            _ => Bus::Sfx,
+        }
+    }
+
+    fn volume(self) -> f32 {
+        match self {
+            SfxId::Click => 0.3,
+            _ => 1.,
+        }
+    }
+}
+
+impl MusicId {
+    const ALL: [MusicId; 3] = [MusicId::Zenith, MusicId::Banger, MusicId::GameOverSting];
+
+    fn path(self) -> &'static str {
+        match self {
+            MusicId::Zenith => "zenith.ogg",
+            MusicId::Banger => "banger.ogg",
+            // placeholder: there's no sting yet, the damage sound stands in
+            MusicId::GameOverSting => "damage.ogg",
+        }
+    }
+
+    fn looping(self) -> bool {
+        !matches!(self, MusicId::GameOverSting)
+    }
+}
+
+fn load_audio_handles(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    let mut handles = AudioHandles {
+        hum: pitches.add(Pitch::new(HUM_FREQUENCY, Duration::from_secs(1))),
+        ..default()
+    };
+    for id in SfxId::ALL {
+        handles.sfx.insert(id, asset_server.load(id.path()));
+    }
+    for id in MusicId::ALL {
+        handles.music.insert(id, asset_server.load(id.path()));
+    }
+    commands.insert_resource(handles);
+}
+
+fn play_sfx_system(
+    mut commands: Commands,
+    mut play_sfx: EventReader<PlaySfx>,
+    mut play_sfx_at: EventReader<PlaySfxAt>,
+    voices: Query<(Entity, &Voice), Without<EnemyHum>>,
+    handles: Res<AudioHandles>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let mut playing: Vec<(Entity, f32)> = voices
+        .iter()
+        .filter(|(_, voice)| voice.bus != Bus::Music)
+        .map(|(entity, voice)| (entity, voice.started))
+        .collect();
+    playing.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let requests = play_sfx.read().map(|PlaySfx(id)| (*id, None)).chain(
+        play_sfx_at
+            .read()
+            .map(|PlaySfxAt(id, pos)| (*id, Some(*pos))),
+    );
+
+    for (id, pos) in requests {
+        let Some(source) = handles.sfx.get(&id) else {
+            continue;
+        };
+        if playing.len() >= MAX_SFX_VOICES {
+            let (oldest, _) = playing.remove(0);
+            commands.entity(oldest).despawn_recursive();
+        }
+
+        let voice = Voice {
+            bus: id.bus(),
+            volume: id.volume(),
+            started: time.elapsed_seconds(),
+        };
+        let mut playback = PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(voice.bus.gain(&settings) * voice.volume));
+        if pos.is_some() {
+            playback = playback
+                .with_spatial(true)
+                .with_spatial_scale(SpatialScale::new(SFX_SPATIAL_SCALE));
+        }
+        let entity = commands
+            .spawn(AudioBundle {
+                source: source.clone(),
+                settings: playback,
+            })
+            .insert(voice)
+            .insert(TransformBundle::from_transform(
+                Transform::from_translation(pos.unwrap_or_default()),
+            ))
+            .id();
+        playing.push((entity, time.elapsed_seconds()));
+    }
+}
+
+// placeholder: there's no menu track yet, so the menu plays the calm in game one
+fn menu_music_system(mut play_music: EventWriter<PlayMusic>) {
+    play_music.send(PlayMusic(MusicId::Zenith));
+}
+
+fn gameover_music_system(mut play_music: EventWriter<PlayMusic>) {
+    play_music.send(PlayMusic(MusicId::GameOverSting));
+}
+
+fn ingame_music_system(
+    enemy_state: Res<EnemyState>,
+    current: Res<CurrentMusic>,
+    mut play_music: EventWriter<PlayMusic>,
+) {
+    // counted from the field itself, the endless curve never touches `enemy_count`
+    let live = enemy_state.pos.len();
+    let track = match current.0 {
+        Some(MusicId::Banger) if live >= CALM_ENEMY_COUNT => MusicId::Banger,
+        _ if live >= INTENSE_ENEMY_COUNT => MusicId::Banger,
+        _ => MusicId::Zenith,
+    };
+    if current.0 != Some(track) {
+        play_music.send(PlayMusic(track));
+    }
+}
+
+fn play_music_system(
+    mut commands: Commands,
+    mut play_music: EventReader<PlayMusic>,
+    mut fades: Query<&mut MusicFade>,
+    mut current: ResMut<CurrentMusic>,
+    handles: Res<AudioHandles>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let Some(PlayMusic(id)) = play_music.read().last() else {
+        return;
+    };
+    if current.0 == Some(*id) {
+        return;
+    }
+    let Some(source) = handles.music.get(id) else {
+        return;
+    };
+
+    for mut fade in fades.iter_mut() {
+        fade.target = 0.;
+    }
+
+    // stings cut straight in, tracks fade in over the outgoing one
+    let (playback, level) = if id.looping() {
+        (PlaybackSettings::LOOP, 0.)
+    } else {
+        (PlaybackSettings::DESPAWN, 1.)
+    };
+    commands.spawn((
+        AudioBundle {
+            source: source.clone(),
+            settings: playback.with_volume(Volume::new(Bus::Music.gain(&settings) * level)),
+        },
+        Voice {
+            bus: Bus::Music,
+            volume: 1.,
+            started: time.elapsed_seconds(),
+        },
+        MusicFade { level, target: 1. },
+    ));
+    current.0 = Some(*id);
+}
+
+fn crossfade_music_system(
+    mut commands: Commands,
+    mut music: Query<(Entity, &Voice, &mut MusicFade, Option<&AudioSink>)>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let step = time.delta_seconds() / MUSIC_FADE_SECS;
+    for (entity, voice, mut fade, sink) in music.iter_mut() {
+        fade.level = if fade.level < fade.target {
+            (fade.level + step).min(fade.target)
+        } else {
+            (fade.level - step).max(fade.target)
+        };
+
+        if fade.level <= 0. && fade.target <= 0. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(voice.bus.gain(&settings) * voice.volume * fade.level);
+        }
+    }
+}
+
+// every enemy carries a looping hum that follows it and rises in pitch with its speed
+fn attach_enemy_hum_system(
+    mut commands: Commands,
+    enemies: Query<(Entity, &MovementSpeed), Added<Enemy>>,
+    handles: Res<AudioHandles>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    for (enemy, ms) in &enemies {
+        let voice = Voice {
+            bus: Bus::Sfx,
+            volume: HUM_VOLUME,
+            started: time.elapsed_seconds(),
+        };
+        let hum = commands
+            .spawn((
+                PitchBundle {
+                    source: handles.hum.clone(),
+                    settings: PlaybackSettings::LOOP
+                        .with_volume(Volume::new(voice.bus.gain(&settings) * voice.volume))
+                        .with_speed(hum_pitch(ms.0))
+                        .with_spatial(true)
+                        .with_spatial_scale(SpatialScale::new(HUM_SPATIAL_SCALE)),
+                },
+                SpatialBundle::default(),
+                voice,
+                EnemyHum,
+            ))
+            .id();
+        commands.entity(enemy).add_child(hum);
+    }
+}
+
+// tracks the enemy's speed and how fast game time runs, so anything that
+// slows the game down drags the pitch down with it
+fn enemy_hum_pitch_system(
+    hums: Query<(&Parent, &SpatialAudioSink), With<EnemyHum>>,
+    enemies: Query<&MovementSpeed>,
+    time: Res<Time<Virtual>>,
+) {
+    for (enemy, sink) in &hums {
+        if let Ok(ms) = enemies.get(enemy.get()) {
+            sink.set_speed(hum_pitch(ms.0 * time.relative_speed()));
+        }
+    }
+}
+
+fn hum_pitch(speed: f32) -> f32 {
+    (speed / HUM_BASE_SPEED).max(HUM_MIN_PITCH)
+}
+
+fn apply_bus_volume_system(
+    settings: Res<Settings>,
+    voices: Query<(
+        Option<&AudioSink>,
+        Option<&SpatialAudioSink>,
+        &Voice,
+        Option<&MusicFade>,
+    )>,
+) {
+    for (sink, spatial_sink, voice, fade) in &voices {
+        let fade = fade.map_or(1., |fade| fade.level);
+        let volume = voice.bus.gain(&settings) * voice.volume * fade;
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
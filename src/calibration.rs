@@ -0,0 +1,225 @@
+use crate::gesture::GestureAction;
+use crate::input::TrackerMessage;
+use crate::menu::{despawn_screen, screen_bundle, spawn_title};
+use crate::save::SaveEvent;
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
+
+// where the player is asked to aim, 0..1 across the window with y down
+const TARGETS: [Vec2; 5] = [
+    Vec2::new(0.1, 0.1),
+    Vec2::new(0.9, 0.1),
+    Vec2::new(0.9, 0.9),
+    Vec2::new(0.1, 0.9),
+    Vec2::new(0.5, 0.5),
+];
+// a capture averages the tracker over this many seconds to iron out jitter
+const SAMPLE_WINDOW: f32 = 0.3;
+const TARGET_SIZE: f32 = 40.;
+
+pub struct CalibrationPlugin;
+
+impl Plugin for CalibrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<CalibrationState>()
+            .add_systems(OnEnter(CalibrationState::Running), init_calibration)
+            .add_systems(
+                OnExit(CalibrationState::Running),
+                despawn_screen::<CalibrationMarker>,
+            )
+            .add_systems(
+                Update,
+                (
+                    collect_tracker_samples_system,
+                    capture_target_system,
+                    refresh_calibration_screen_system,
+                )
+                    .chain()
+                    .run_if(in_state(CalibrationState::Running)),
+            );
+    }
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CalibrationState {
+    #[default]
+    Off,
+    Running,
+}
+
+// maps raw tracker coordinates onto the window, both 0..1 with y down
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrackerCalibration {
+    pub x: [f32; 3],
+    pub y: [f32; 3],
+}
+
+#[derive(Resource, Default)]
+struct CalibrationRun {
+    // (raw tracker position, target it was aimed at)
+    captured: Vec<(Vec2, Vec2)>,
+    // (real seconds, raw tracker position)
+    recent: Vec<(f32, Vec2)>,
+    status: String,
+}
+
+#[derive(Component)]
+struct CalibrationMarker;
+
+#[derive(Component)]
+struct CalibrationTarget;
+
+#[derive(Component)]
+struct CalibrationText;
+
+impl TrackerCalibration {
+    pub fn apply(&self, raw: Vec2) -> Vec2 {
+        let raw = raw.extend(1.);
+        Vec2::new(Vec3::from(self.x).dot(raw), Vec3::from(self.y).dot(raw))
+    }
+
+    // least squares affine fit, None when the points are degenerate (all in a line)
+    pub fn fit(pairs: &[(Vec2, Vec2)]) -> Option<Self> {
+        let mut normal = Mat3::ZERO;
+        let mut rhs_x = Vec3::ZERO;
+        let mut rhs_y = Vec3::ZERO;
+        for (raw, target) in pairs {
+            let raw = raw.extend(1.);
+            normal += Mat3::from_cols(raw * raw.x, raw * raw.y, raw);
+            rhs_x += raw * target.x;
+            rhs_y += raw * target.y;
+        }
+        if normal.determinant().abs() < 1e-6 {
+            return None;
+        }
+        let inverse = normal.inverse();
+        Some(Self {
+            x: (inverse * rhs_x).to_array(),
+            y: (inverse * rhs_y).to_array(),
+        })
+    }
+}
+
+fn init_calibration(mut commands: Commands) {
+    commands.insert_resource(CalibrationRun::default());
+
+    let screen = commands
+        .spawn((
+            CalibrationMarker,
+            screen_bundle(Color::srgba(0., 0., 0., 0.9)),
+        ))
+        // above everything, and keep clicks from reaching the menu underneath
+        .insert(ZIndex::Global(20))
+        .insert(FocusPolicy::Block)
+        .id();
+    spawn_title(&mut commands, screen, "Tracker Calibration");
+
+    let text = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: 28.,
+            ..default()
+        },
+    )
+    .with_text_justify(JustifyText::Center);
+    let text = commands.spawn((text, CalibrationText)).id();
+
+    let target = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(TARGET_SIZE),
+            height: Val::Px(TARGET_SIZE),
+            margin: UiRect {
+                left: Val::Px(-TARGET_SIZE / 2.),
+                top: Val::Px(-TARGET_SIZE / 2.),
+                ..default()
+            },
+            border: UiRect::all(Val::Px(5.)),
+            ..default()
+        },
+        border_radius: BorderRadius::all(Val::Percent(50.)),
+        border_color: BorderColor(Color::srgb(1., 0.85, 0.2)),
+        ..default()
+    };
+    let target = commands.spawn((target, CalibrationTarget)).id();
+    commands.entity(screen).push_children(&[text, target]);
+}
+
+fn collect_tracker_samples_system(
+    mut run: ResMut<CalibrationRun>,
+    mut messages: EventReader<TrackerMessage>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    for message in messages.read() {
+        if let TrackerMessage::Aim(raw) = message {
+            run.recent.push((now, *raw));
+        }
+    }
+    run.recent.retain(|(at, _)| now - at <= SAMPLE_WINDOW);
+}
+
+fn capture_target_system(
+    mut run: ResMut<CalibrationRun>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut gestures: EventReader<GestureAction>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<CalibrationState>>,
+    mut save_event: EventWriter<SaveEvent>,
+) {
+    let pinched = gestures.read().any(|action| *action == GestureAction::Fire);
+    if !pinched && !mouse.just_pressed(MouseButton::Left) && !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    if run.recent.is_empty() {
+        run.status = "No tracker input, is the tracker running?".to_string();
+        return;
+    }
+
+    let sum: Vec2 = run.recent.iter().map(|(_, raw)| *raw).sum();
+    let raw = sum / run.recent.len() as f32;
+    let target = TARGETS[run.captured.len()];
+    run.captured.push((raw, target));
+    run.status.clear();
+    if run.captured.len() < TARGETS.len() {
+        return;
+    }
+
+    match TrackerCalibration::fit(&run.captured) {
+        Some(calibration) => {
+            let profile = settings.tracker_profile.clone();
+            settings.tracker_calibrations.insert(profile, calibration);
+            save_event.send(SaveEvent);
+            next_state.set(CalibrationState::Off);
+        }
+        None => {
+            run.captured.clear();
+            run.status = "Those points were too close together, let's start over".to_string();
+        }
+    }
+}
+
+fn refresh_calibration_screen_system(
+    run: Res<CalibrationRun>,
+    settings: Res<Settings>,
+    mut target: Query<&mut Style, With<CalibrationTarget>>,
+    mut text: Query<&mut Text, With<CalibrationText>>,
+) {
+    let step = run.captured.len().min(TARGETS.len() - 1);
+    for mut style in target.iter_mut() {
+        style.left = Val::Percent(TARGETS[step].x * 100.);
+        style.top = Val::Percent(TARGETS[step].y * 100.);
+    }
+    for mut text in text.iter_mut() {
+        text.sections[0].value = format!(
+            "Profile \"{}\", target {}/{}\nAim at the ring and pinch (or press Space)\nEscape to cancel\n{}",
+            settings.tracker_profile,
+            step + 1,
+            TARGETS.len(),
+            run.status,
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::gesture::{gesture_system, GestureAction, Gestures};
+use crate::globals::{GameState, Kulay};
+use crate::input::{tracker_to_window, FilterState, TrackerMessage};
+use crate::mode::GameMode;
+use crate::player::{PlayerWeapon, Shooter, Trigger};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+// px per second at full stick
+const STICK_SPEED: f32 = 900.;
+
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SecondPlayer::default())
+            .add_systems(OnEnter(GameState::InGame), init_second_player)
+            .add_systems(
+                PreUpdate,
+                second_player_input_system
+                    .after(gesture_system)
+                    .run_if(|mode: Res<GameMode>| mode.players() > 1),
+            );
+    }
+}
+
+// player two's controls for this frame, from the first gamepad and the hand tracker
+#[derive(Resource, Default)]
+pub struct SecondPlayer {
+    // where their crosshair sits and shots go, in window coordinates
+    pub pos: Option<Vec2>,
+    pub trigger: Trigger,
+    pub color: Option<Kulay>,
+    pub cycle_weapon: bool,
+    pub reload: bool,
+    filter: FilterState,
+}
+
+// player one's gun lives on the camera; player two's is its own entity,
+// only around while co-op is being played
+fn init_second_player(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut second: ResMut<SecondPlayer>,
+    weapons: Query<(Entity, &Shooter), With<PlayerWeapon>>,
+) {
+    *second = SecondPlayer::default();
+    let existing = weapons
+        .iter()
+        .find(|(_, shooter)| **shooter == Shooter::Two)
+        .map(|(entity, _)| entity);
+    match (existing, mode.players() > 1) {
+        (None, true) => {
+            commands.spawn((PlayerWeapon::new(0, Kulay::Pula), Shooter::Two));
+        }
+        (Some(entity), false) => commands.entity(entity).despawn_recursive(),
+        _ => {}
+    }
+}
+
+fn second_player_input_system(
+    mut second: ResMut<SecondPlayer>,
+    mut messages: EventReader<TrackerMessage>,
+    mut gesture_actions: EventReader<GestureAction>,
+    gestures: Res<Gestures>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    win: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let Ok(win) = win.get_single() else {
+        return;
+    };
+    let mut pos = second.pos.unwrap_or(win.size() / 2.);
+    let mut trigger = Trigger::default();
+    let mut color = None;
+    let (mut cycle_weapon, mut reload) = (false, false);
+
+    if let Some(gamepad) = gamepads.iter().next() {
+        let button = |kind| GamepadButton::new(gamepad, kind);
+        let axis = |kind| axes.get(GamepadAxis::new(gamepad, kind)).unwrap_or(0.);
+        let stick = Vec2::new(
+            axis(GamepadAxisType::RightStickX),
+            // the stick points y up, the window y down
+            -axis(GamepadAxisType::RightStickY),
+        );
+        if stick != Vec2::ZERO {
+            pos += stick * STICK_SPEED * time.delta_seconds();
+            // the stick moves the crosshair directly, so the tracker's smoothing starts over
+            second.filter = FilterState::default();
+        }
+
+        let fire = button(GamepadButtonType::RightTrigger2);
+        trigger.just_pressed = buttons.just_pressed(fire);
+        trigger.held = buttons.pressed(fire);
+        trigger.just_released = buttons.just_released(fire);
+        // face buttons by the colors printed on them
+        color = [
+            (GamepadButtonType::West, Kulay::Asul),
+            (GamepadButtonType::East, Kulay::Pula),
+            (GamepadButtonType::North, Kulay::Dilaw),
+        ]
+        .into_iter()
+        .find(|(kind, _)| buttons.just_pressed(button(*kind)))
+        .map(|(_, color)| color);
+        cycle_weapon = buttons.just_pressed(button(GamepadButtonType::RightTrigger));
+        reload = buttons.just_pressed(button(GamepadButtonType::South));
+    }
+
+    let tracked = messages
+        .read()
+        .filter_map(|message| match message {
+            TrackerMessage::Aim(raw) => Some(tracker_to_window(&settings, win, *raw)),
+            _ => None,
+        })
+        .last();
+    if let Some(raw) = tracked {
+        pos = second
+            .filter
+            .apply(&settings.input_filters.tracker, raw, time.elapsed_seconds());
+    }
+    // the pinch and fist work the same as they do for a lone player
+    for action in gesture_actions.read() {
+        match action {
+            GestureAction::Fire => trigger.just_pressed = true,
+            GestureAction::CeaseFire => trigger.just_released = true,
+            GestureAction::SelectWeapon(picked) => color = color.or(Some(*picked)),
+        }
+    }
+    trigger.held |= gestures.pinch.on;
+
+    second.pos = Some(pos.clamp(Vec2::ZERO, win.size()));
+    second.trigger = trigger;
+    second.color = color;
+    second.cycle_weapon = cycle_weapon;
+    second.reload = reload;
+}
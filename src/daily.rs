@@ -0,0 +1,113 @@
+use crate::enemy::WaveConfig;
+use crate::globals::Kulay;
+use crate::save::unix_now;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+const SECS_PER_DAY: u64 = 86_400;
+// kept apart from the spawn seed so modifiers and spawns don't move in lockstep
+const MODIFIER_SALT: u64 = 0x0064_6169_6c79;
+const FAST_ENEMY_SPEED: f32 = 1.4;
+// how long a kill holds the weapon color in place with `Modifier::SwitchLock`
+pub const SWITCH_LOCK_SECS: f32 = 5.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    FastEnemies,
+    ThreeColors,
+    SwitchLock,
+    MirroredAim,
+}
+
+// the same for everyone on a given UTC day
+#[derive(Debug, Clone)]
+pub struct DailyChallenge {
+    // days since the unix epoch
+    pub day: u32,
+    pub modifiers: Vec<Modifier>,
+}
+
+// the challenge the current run is playing, None outside of daily runs
+#[derive(Resource, Default)]
+pub struct DailyRun {
+    pub challenge: Option<DailyChallenge>,
+    // only the first finished run of the day counts for the leaderboard
+    pub official: bool,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 4] = [
+        Modifier::FastEnemies,
+        Modifier::ThreeColors,
+        Modifier::SwitchLock,
+        Modifier::MirroredAim,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Modifier::FastEnemies => "Fast Enemies",
+            Modifier::ThreeColors => "Three Colors",
+            Modifier::SwitchLock => "Kills Lock Your Color",
+            Modifier::MirroredAim => "Mirrored Aim",
+        }
+    }
+
+    // the modifiers that change what spawns; the rest are checked where they apply
+    pub fn apply(self, wave_config: &mut WaveConfig) {
+        match self {
+            Modifier::FastEnemies => wave_config.speed_scale *= FAST_ENEMY_SPEED,
+            Modifier::ThreeColors => wave_config.colors = Kulay::ALL.to_vec(),
+            Modifier::SwitchLock | Modifier::MirroredAim => {}
+        }
+    }
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_day((unix_now() / SECS_PER_DAY) as u32)
+    }
+
+    pub fn for_day(day: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(day as u64 ^ MODIFIER_SALT);
+        let count = rng.gen_range(1..=2);
+        let modifiers = Modifier::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect();
+        Self { day, modifiers }
+    }
+
+    pub fn spawn_seed(&self) -> u64 {
+        self.day as u64
+    }
+
+    // yyyy-mm-dd, from Howard Hinnant's civil_from_days
+    pub fn date(&self) -> String {
+        let z = self.day as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    pub fn describe(&self) -> String {
+        let labels: Vec<&str> = self.modifiers.iter().map(|m| m.label()).collect();
+        labels.join(", ")
+    }
+}
+
+impl DailyRun {
+    pub fn has(&self, modifier: Modifier) -> bool {
+        self.challenge
+            .as_ref()
+            .is_some_and(|challenge| challenge.modifiers.contains(&modifier))
+    }
+}
@@ -0,0 +1,320 @@
+use crate::globals::Kulay;
+use crate::hud::CrossHairMarker;
+use crate::player::{CamMarker, Shooter};
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+// how far below and ahead of the camera tracers start, so they don't begin
+// inside the near plane
+const TRACER_DROP: f32 = 0.3;
+const TRACER_LEAD: f32 = 0.5;
+const PARTICLE_GRAVITY: f32 = 9.8;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EffectsConfig::default())
+            .add_event::<EffectEvent>()
+            .add_systems(Startup, init_effect_assets)
+            .add_systems(
+                Update,
+                (
+                    spawn_effect_system,
+                    animate_particle_system,
+                    animate_tracer_system,
+                    animate_hit_marker_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct EffectsConfig {
+    pub burst_count: usize,
+    pub burst_speed: f32,
+    pub burst_lifetime: f32,
+    pub spark_count: usize,
+    pub spark_speed: f32,
+    pub spark_lifetime: f32,
+    pub tracer_lifetime: f32,
+    pub hit_marker_lifetime: f32,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            burst_count: 24,
+            burst_speed: 8.,
+            burst_lifetime: 0.6,
+            spark_count: 8,
+            spark_speed: 12.,
+            spark_lifetime: 0.25,
+            tracer_lifetime: 0.12,
+            hit_marker_lifetime: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitMarker {
+    Kill,
+    Deflect,
+}
+
+#[derive(Event)]
+pub enum EffectEvent {
+    Burst { pos: Vec3, color: Kulay },
+    Deflect { pos: Vec3 },
+    Pickup { pos: Vec3 },
+    Tracer { from: Vec3, to: Vec3 },
+    // flashes the crosshair of the player who landed the shot
+    HitMarker(HitMarker, Shooter),
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+    lifetime: Timer,
+    size: f32,
+}
+
+#[derive(Component)]
+struct Tracer {
+    lifetime: Timer,
+    width: f32,
+}
+
+#[derive(Component)]
+struct HitMarkerFlash {
+    lifetime: Timer,
+    color: Color,
+}
+
+struct ParticleSpec {
+    count: usize,
+    speed: f32,
+    lifetime: f32,
+    size: f32,
+}
+
+#[derive(Resource)]
+struct EffectAssets {
+    // unit cube, scaled per particle and stretched along z for tracers
+    cube: Handle<Mesh>,
+    pula: Handle<StandardMaterial>,
+    asul: Handle<StandardMaterial>,
+    dilaw: Handle<StandardMaterial>,
+    spark: Handle<StandardMaterial>,
+    pickup: Handle<StandardMaterial>,
+    tracer_material: Handle<StandardMaterial>,
+}
+
+impl HitMarker {
+    fn color(self) -> Color {
+        match self {
+            HitMarker::Kill => Color::WHITE,
+            HitMarker::Deflect => Color::srgb(1., 0.6, 0.1),
+        }
+    }
+}
+
+fn unlit(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        emissive: color.into(),
+        unlit: true,
+        ..default()
+    }
+}
+
+fn init_effect_assets(
+    mut commands: Commands,
+    mut mesh: ResMut<Assets<Mesh>>,
+    mut material: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(EffectAssets {
+        cube: mesh.add(Cuboid::from_length(1.)),
+        pula: material.add(unlit(Kulay::Pula.color())),
+        asul: material.add(unlit(Kulay::Asul.color())),
+        dilaw: material.add(unlit(Kulay::Dilaw.color())),
+        spark: material.add(unlit(HitMarker::Deflect.color())),
+        pickup: material.add(unlit(Color::WHITE)),
+        tracer_material: material.add(unlit(Color::srgb(1., 0.95, 0.7))),
+    });
+}
+
+fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    loop {
+        let dir = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        if let Some(dir) = dir.try_normalize() {
+            return dir;
+        }
+    }
+}
+
+fn spawn_particles(
+    commands: &mut Commands,
+    assets: &EffectAssets,
+    material: Handle<StandardMaterial>,
+    pos: Vec3,
+    spec: ParticleSpec,
+) {
+    let mut rng = thread_rng();
+    for _ in 0..spec.count {
+        let velocity = random_direction(&mut rng) * spec.speed * rng.gen_range(0.5..1.);
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.cube.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(pos).with_scale(Vec3::splat(spec.size)),
+                ..default()
+            },
+            Particle {
+                velocity,
+                lifetime: Timer::from_seconds(spec.lifetime, TimerMode::Once),
+                size: spec.size,
+            },
+        ));
+    }
+}
+
+fn spawn_effect_system(
+    mut commands: Commands,
+    mut effects: EventReader<EffectEvent>,
+    assets: Res<EffectAssets>,
+    config: Res<EffectsConfig>,
+    cam: Query<&GlobalTransform, With<CamMarker>>,
+    crosshair: Query<(Entity, &Shooter), With<CrossHairMarker>>,
+) {
+    for effect in effects.read() {
+        match effect {
+            EffectEvent::Burst { pos, color } => {
+                let material = match color {
+                    Kulay::Pula => assets.pula.clone(),
+                    Kulay::Asul => assets.asul.clone(),
+                    Kulay::Dilaw => assets.dilaw.clone(),
+                };
+                let spec = ParticleSpec {
+                    count: config.burst_count,
+                    speed: config.burst_speed,
+                    lifetime: config.burst_lifetime,
+                    size: 0.2,
+                };
+                spawn_particles(&mut commands, &assets, material, *pos, spec);
+            }
+            EffectEvent::Deflect { pos } => {
+                let spec = ParticleSpec {
+                    count: config.spark_count,
+                    speed: config.spark_speed,
+                    lifetime: config.spark_lifetime,
+                    size: 0.08,
+                };
+                spawn_particles(&mut commands, &assets, assets.spark.clone(), *pos, spec);
+            }
+            EffectEvent::Pickup { pos } => {
+                let spec = ParticleSpec {
+                    count: config.burst_count,
+                    speed: config.burst_speed,
+                    lifetime: config.burst_lifetime,
+                    size: 0.12,
+                };
+                spawn_particles(&mut commands, &assets, assets.pickup.clone(), *pos, spec);
+            }
+            EffectEvent::Tracer { from, to } => {
+                let (up, forward) = cam
+                    .get_single()
+                    .map(|cam| (*cam.up(), *cam.forward()))
+                    .unwrap_or((Vec3::Y, Vec3::NEG_Z));
+                let from = *from - up * TRACER_DROP + forward * TRACER_LEAD;
+                let length = from.distance(*to);
+                if length <= f32::EPSILON {
+                    continue;
+                }
+                let width = 0.04;
+                let transform = Transform::from_translation(from.lerp(*to, 0.5))
+                    .looking_at(*to, up)
+                    .with_scale(Vec3::new(width, width, length));
+                commands.spawn((
+                    PbrBundle {
+                        mesh: assets.cube.clone(),
+                        material: assets.tracer_material.clone(),
+                        transform,
+                        ..default()
+                    },
+                    Tracer {
+                        lifetime: Timer::from_seconds(config.tracer_lifetime, TimerMode::Once),
+                        width,
+                    },
+                ));
+            }
+            EffectEvent::HitMarker(marker, shooter) => {
+                for (crosshair, _) in crosshair.iter().filter(|(_, s)| *s == shooter) {
+                    commands.entity(crosshair).insert(HitMarkerFlash {
+                        lifetime: Timer::from_seconds(config.hit_marker_lifetime, TimerMode::Once),
+                        color: marker.color(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn animate_particle_system(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Transform, &mut Particle)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut particle) in particles.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        particle.velocity.y -= PARTICLE_GRAVITY * time.delta_seconds();
+        transform.translation += particle.velocity * time.delta_seconds();
+        transform.scale = Vec3::splat(particle.size * particle.lifetime.fraction_remaining());
+    }
+}
+
+fn animate_tracer_system(
+    mut commands: Commands,
+    mut tracers: Query<(Entity, &mut Transform, &mut Tracer)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut tracer) in tracers.iter_mut() {
+        tracer.lifetime.tick(time.delta());
+        if tracer.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let width = tracer.width * tracer.lifetime.fraction_remaining();
+        transform.scale.x = width;
+        transform.scale.y = width;
+    }
+}
+
+fn animate_hit_marker_system(
+    mut commands: Commands,
+    mut crosshair: Query<(Entity, &mut BackgroundColor, &mut HitMarkerFlash)>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, mut background, mut flash) in crosshair.iter_mut() {
+        flash.lifetime.tick(time.delta());
+        if flash.lifetime.finished() {
+            *background = BackgroundColor(Color::NONE);
+            commands.entity(entity).remove::<HitMarkerFlash>();
+            continue;
+        }
+        *background = BackgroundColor(
+            flash
+                .color
+                .with_alpha(0.8 * flash.lifetime.fraction_remaining()),
+        );
+    }
+}
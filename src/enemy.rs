@@ -2,20 +2,34 @@ use crate::{
     globals::reset_system,
     globals::{GameState, Kulay},
     player::KillCount,
+    powerup::PowerUpKind,
 };
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, HashSet};
 
 pub const ENEMY_SPAWN_LIMIT: usize = 10;
 const KILL_LEVELS: [usize; ENEMY_SPAWN_LIMIT - 1] = [5, 10, 50, 100, 150, 250, 300, 350, 400];
+// spawn grid, x across and y up; also the most enemies that fit on the field at once
+const SPAWN_X: (i32, i32) = (-9, 9);
+const SPAWN_Y: (i32, i32) = (3, 8);
+const SPAWN_SLOTS: usize = ((SPAWN_X.1 - SPAWN_X.0 + 1) * (SPAWN_Y.1 - SPAWN_Y.0 + 1)) as usize;
+// how far a weaver sways either side, and how quickly (radians per second)
+const WEAVE_AMPLITUDE: f32 = 2.;
+const WEAVE_FREQUENCY: f32 = 1.5;
+// mixed into a run's seed, one per stream in `SpawnRng`
+const ENEMY_SALT: u64 = 0x656e_656d_7921;
+const POWERUP_SALT: u64 = 0x706f_7765_7275;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(EnemyState::default())
+            .insert_resource(WaveConfig::default())
+            .insert_resource(SpawnRng::from_entropy())
             .add_systems(
                 Update,
                 (
@@ -36,13 +50,77 @@ impl Plugin for EnemyPlugin {
 #[derive(Bundle, Debug)]
 pub struct EnemyBundle {
     pub color: Kulay,
+    pub archetype: Archetype,
     pub marker: Enemy,
     pub ms: MovementSpeed,
+    pub spawned_at: SpawnedAt,
 }
 
 #[derive(Component, Debug)]
 pub struct Enemy;
 
+// how an enemy is built and moves
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archetype {
+    Grunt,
+    // small and quick
+    Runner,
+    // sways side to side on the way in
+    Weaver,
+}
+
+// knobs for what shows up in a run besides the enemies themselves
+#[derive(Resource, Clone, Debug)]
+pub struct WaveConfig {
+    // kept on the field on top of what the kill count calls for
+    pub extra_enemies: usize,
+    // a fixed number to keep on the field, in place of the kill levels
+    pub enemy_count: Option<usize>,
+    // seconds between spawns when topping the field back up, all at once when None
+    pub spawn_interval: Option<f32>,
+    pub colors: Vec<Kulay>,
+    // each spawn picks one of these evenly
+    pub archetypes: Vec<Archetype>,
+    // multiplies every enemy's speed
+    pub speed_scale: f32,
+    pub powerups_per_minute: Vec<(PowerUpKind, f32)>,
+    // replaces the kill levels with a ramp that never stops
+    pub curve: Option<DifficultyCurve>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum WaveProgress {
+    Kills(usize),
+    Seconds(f32),
+}
+
+// every field grows linearly with the wave number, with nothing capping it
+#[derive(Clone, Debug)]
+pub struct DifficultyCurve {
+    // how long a wave lasts
+    pub wave_length: WaveProgress,
+    pub base_enemies: f32,
+    pub enemies_per_wave: f32,
+    pub base_speed: (f32, f32),
+    pub speed_per_wave: (f32, f32),
+    // seconds between spawns on the first wave, shrinking as 1 / (1 + growth * wave)
+    pub spawn_interval: f32,
+    pub cadence_growth: f32,
+    // waves on which one more color joins the two every run starts with
+    pub color_waves: Vec<usize>,
+    // waves on which each archetype joins the grunts
+    pub archetype_waves: Vec<(usize, Archetype)>,
+}
+
+// everything about where and what spawns comes from here, so a seeded run
+// plays out the same for everyone; enemies and power-ups draw from separate
+// streams so one can't shift the other
+#[derive(Resource)]
+pub struct SpawnRng {
+    pub enemies: StdRng,
+    pub powerups: StdRng,
+}
+
 #[derive(Debug)]
 pub struct EnemyPos {
     pub x: i32,
@@ -52,11 +130,20 @@ pub struct EnemyPos {
 #[derive(Component, Debug)]
 pub struct MovementSpeed(pub f32);
 
+// `EnemyState::elapsed` when the enemy showed up
+#[derive(Component, Debug)]
+pub struct SpawnedAt(pub f32);
+
 #[derive(Resource)]
 pub struct EnemyState {
     pub pos: HashMap<Entity, EnemyPos>,
     pub enemy_count: usize,
     pub enemy_count_updated: bool,
+    // only counted with a difficulty curve, from 1
+    pub wave: usize,
+    // game seconds into the run
+    pub elapsed: f32,
+    spawn_cooldown: f32,
 }
 
 impl Default for EnemyState {
@@ -65,16 +152,135 @@ impl Default for EnemyState {
             pos: HashMap::new(),
             enemy_count: 1,
             enemy_count_updated: false,
+            wave: 1,
+            elapsed: 0.,
+            spawn_cooldown: 0.,
+        }
+    }
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        Self {
+            extra_enemies: 0,
+            enemy_count: None,
+            spawn_interval: None,
+            colors: vec![Kulay::Pula, Kulay::Asul],
+            archetypes: vec![Archetype::Grunt],
+            speed_scale: 1.,
+            powerups_per_minute: vec![
+                (PowerUpKind::Heart, 1.),
+                (PowerUpKind::Shield, 1.),
+                (PowerUpKind::SlowMotion, 0.5),
+                (PowerUpKind::Wildcard, 0.75),
+                (PowerUpKind::Piercing, 0.75),
+            ],
+            curve: None,
+        }
+    }
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            wave_length: WaveProgress::Kills(15),
+            base_enemies: 2.,
+            enemies_per_wave: 0.75,
+            base_speed: (7., 9.),
+            speed_per_wave: (0.4, 0.6),
+            spawn_interval: 1.,
+            cadence_growth: 0.15,
+            color_waves: vec![4],
+            archetype_waves: vec![(3, Archetype::Runner), (6, Archetype::Weaver)],
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub fn wave(&self, kills: usize, elapsed: f32) -> usize {
+        let done = match self.wave_length {
+            WaveProgress::Kills(per_wave) => kills / per_wave.max(1),
+            WaveProgress::Seconds(per_wave) => (elapsed / per_wave) as usize,
+        };
+        done + 1
+    }
+
+    fn enemy_count(&self, wave: usize) -> usize {
+        let count = self.base_enemies + self.enemies_per_wave * (wave - 1) as f32;
+        (count as usize).min(SPAWN_SLOTS)
+    }
+
+    fn speed(&self, wave: usize) -> (f32, f32) {
+        let grown = (wave - 1) as f32;
+        (
+            self.base_speed.0 + self.speed_per_wave.0 * grown,
+            self.base_speed.1 + self.speed_per_wave.1 * grown,
+        )
+    }
+
+    fn spawn_interval(&self, wave: usize) -> f32 {
+        self.spawn_interval / (1. + self.cadence_growth * (wave - 1) as f32)
+    }
+
+    fn colors(&self, wave: usize) -> Vec<Kulay> {
+        let joined = self.color_waves.iter().filter(|at| **at <= wave).count();
+        Kulay::ALL[..(2 + joined).min(Kulay::ALL.len())].to_vec()
+    }
+
+    // grunts always, plus every archetype whose wave has come
+    fn archetypes(&self, wave: usize) -> Vec<Archetype> {
+        let joined = self
+            .archetype_waves
+            .iter()
+            .filter(|(at, _)| *at <= wave)
+            .map(|(_, archetype)| *archetype);
+        std::iter::once(Archetype::Grunt).chain(joined).collect()
+    }
+}
+
+impl Archetype {
+    pub const ALL: [Archetype; 3] = [Archetype::Grunt, Archetype::Runner, Archetype::Weaver];
+
+    fn radius(self) -> f32 {
+        match self {
+            Archetype::Grunt | Archetype::Weaver => 1.,
+            Archetype::Runner => 0.7,
+        }
+    }
+
+    fn speed_scale(self) -> f32 {
+        match self {
+            Archetype::Grunt => 1.,
+            Archetype::Runner => 1.5,
+            Archetype::Weaver => 0.9,
+        }
+    }
+}
+
+impl SpawnRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            enemies: StdRng::seed_from_u64(seed ^ ENEMY_SALT),
+            powerups: StdRng::seed_from_u64(seed ^ POWERUP_SALT),
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self {
+            enemies: StdRng::from_entropy(),
+            powerups: StdRng::from_entropy(),
         }
     }
 }
 
 impl EnemyBundle {
-    fn new(color: Kulay, ms: f32) -> Self {
+    fn new(color: Kulay, archetype: Archetype, ms: f32, spawned_at: f32) -> Self {
         Self {
             color,
+            archetype,
             marker: Enemy,
             ms: MovementSpeed(ms),
+            spawned_at: SpawnedAt(spawned_at),
         }
     }
 }
@@ -93,11 +299,19 @@ pub fn eliminate_enemy(
 
 // make them strafe to make them appear they're dodging
 fn enemy_movement_system(
-    mut enemies: Query<(&mut Transform, &MovementSpeed), With<Enemy>>,
+    mut enemies: Query<(&mut Transform, &MovementSpeed, &Archetype, &SpawnedAt), With<Enemy>>,
+    enemy_state: Res<EnemyState>,
     time: Res<Time>,
 ) {
-    for (mut pos, ms) in enemies.iter_mut() {
+    for (mut pos, ms, archetype, spawned_at) in enemies.iter_mut() {
         pos.translation.z += ms.0 * time.delta_seconds();
+        if *archetype == Archetype::Weaver {
+            // the rate of change of a sine that starts at the spawn column, so
+            // every weaver sways around where it came in
+            let phase = (enemy_state.elapsed - spawned_at.0) * WEAVE_FREQUENCY;
+            pos.translation.x +=
+                phase.cos() * WEAVE_AMPLITUDE * WEAVE_FREQUENCY * time.delta_seconds();
+        }
     }
 }
 
@@ -107,8 +321,58 @@ pub fn enemy_spawn_system(
     mut material: ResMut<Assets<StandardMaterial>>,
     mut enemy_state: ResMut<EnemyState>,
     player_kill_count: Res<KillCount>,
+    mut wave_config: ResMut<WaveConfig>,
+    mut rng: ResMut<SpawnRng>,
+    time: Res<Time>,
 ) {
-    let mut unique_pos: HashSet<(i32, i32)> = enemy_state
+    let rng = &mut rng.enemies;
+    enemy_state.elapsed += time.delta_seconds();
+    if let Some(curve) = wave_config.curve.clone() {
+        enemy_state.wave = curve.wave(player_kill_count.0, enemy_state.elapsed);
+        enemy_state.spawn_cooldown -= time.delta_seconds();
+        let wave = enemy_state.wave;
+        // the weapon colors on offer follow along with what spawns
+        let colors = curve.colors(wave);
+        if wave_config.colors != colors {
+            wave_config.colors = colors;
+        }
+        let archetypes = curve.archetypes(wave);
+        if wave_config.archetypes != archetypes {
+            wave_config.archetypes = archetypes;
+        }
+        if enemy_state.spawn_cooldown > 0. || enemy_state.pos.len() >= curve.enemy_count(wave) {
+            return;
+        }
+
+        // one at a time, so the cadence is what sets the pace
+        let taken: HashSet<(i32, i32)> = enemy_state
+            .pos
+            .values()
+            .map(|enemy| (enemy.x, enemy.y))
+            .collect();
+        let pos = loop {
+            let pos = random_spawn_pos(rng);
+            if !taken.contains(&pos) {
+                break pos;
+            }
+        };
+        let (min_speed, max_speed) = curve.speed(wave);
+        let ms = rng.gen_range(min_speed..=max_speed);
+        spawn_enemy(
+            &mut commands,
+            &mut mesh,
+            &mut material,
+            &mut enemy_state,
+            &wave_config,
+            rng,
+            pos,
+            ms,
+        );
+        enemy_state.spawn_cooldown = curve.spawn_interval(wave);
+        return;
+    }
+
+    let mut taken: HashSet<(i32, i32)> = enemy_state
         .pos
         .values()
         .map(|enemy| (enemy.x, enemy.y))
@@ -127,62 +391,94 @@ pub fn enemy_spawn_system(
         enemy_state.enemy_count_updated = true;
     }
 
-    let mut rng = thread_rng();
-    while unique_pos.len() < enemy_state.enemy_count {
-        let (x, y) = (rng.gen_range(-9..=9), rng.gen_range(3..=8));
-        unique_pos.insert((x, y));
+    // kept in the order they were rolled so a seeded run spawns the same way every time
+    let mut new_pos = Vec::new();
+    let mut target = wave_config
+        .enemy_count
+        .unwrap_or(enemy_state.enemy_count + wave_config.extra_enemies)
+        .min(SPAWN_SLOTS);
+    if let Some(interval) = wave_config.spawn_interval {
+        enemy_state.spawn_cooldown -= time.delta_seconds();
+        if enemy_state.spawn_cooldown > 0. || taken.len() >= target {
+            return;
+        }
+        target = taken.len() + 1;
+        enemy_state.spawn_cooldown = interval;
     }
-
-    for (x, y) in unique_pos {
-        if enemy_state
-            .pos
-            .iter()
-            .any(|(_, enemy)| enemy.x == x && enemy.y == y)
-        {
-            continue;
+    while taken.len() < target {
+        let pos = random_spawn_pos(rng);
+        if taken.insert(pos) {
+            new_pos.push(pos);
         }
+    }
 
-        // roll the dice
-        let color = if rng.gen_range(0..=1) == 0 {
-            Kulay::Pula
-        } else {
-            Kulay::Asul
-        };
+    for pos in new_pos {
+        let ms = pick_ms(rng, enemy_state.enemy_count);
+        spawn_enemy(
+            &mut commands,
+            &mut mesh,
+            &mut material,
+            &mut enemy_state,
+            &wave_config,
+            rng,
+            pos,
+            ms,
+        );
+    }
+}
+
+fn random_spawn_pos(rng: &mut impl Rng) -> (i32, i32) {
+    (
+        rng.gen_range(SPAWN_X.0..=SPAWN_X.1),
+        rng.gen_range(SPAWN_Y.0..=SPAWN_Y.1),
+    )
+}
+
+fn spawn_enemy(
+    commands: &mut Commands,
+    mesh: &mut Assets<Mesh>,
+    material: &mut Assets<StandardMaterial>,
+    enemy_state: &mut EnemyState,
+    wave_config: &WaveConfig,
+    rng: &mut StdRng,
+    (x, y): (i32, i32),
+    ms: f32,
+) {
+    // roll the dice
+    let color = wave_config.colors[rng.gen_range(0..wave_config.colors.len())];
+    let archetype = wave_config.archetypes[rng.gen_range(0..wave_config.archetypes.len())];
 
-        let sphere = Sphere { radius: 1. };
-        let sphere_bundle = MaterialMeshBundle {
-            mesh: mesh.add(sphere),
-            transform: Transform::from_xyz(x as f32, y as f32, -50.),
-            material: match color {
-                Kulay::Pula => material.add(StandardMaterial {
-                    base_color: Color::hsl(0., 0.5, 0.5),
-                    reflectance: 0.,
-                    ..default()
-                }),
-                Kulay::Asul => material.add(StandardMaterial {
-                    base_color: Color::hsl(240., 0.8, 0.5),
-                    reflectance: 0.,
-                    ..default()
-                }),
-            },
+    let radius = archetype.radius();
+    let sphere = Sphere { radius };
+    let sphere_bundle = MaterialMeshBundle {
+        mesh: mesh.add(sphere),
+        transform: Transform::from_xyz(x as f32, y as f32, -50.),
+        material: material.add(StandardMaterial {
+            base_color: color.color(),
+            reflectance: 0.,
             ..default()
-        };
+        }),
+        ..default()
+    };
 
-        let enemy_id = commands
-            .spawn((
-                EnemyBundle::new(color, pick_ms(enemy_state.enemy_count)),
-                sphere_bundle,
-            ))
-            .insert(Sensor)
-            .insert(Collider::ball(1.))
-            .insert(CollisionGroups::new(Group::GROUP_2, Group::GROUP_1))
-            .id();
-        enemy_state.pos.insert(enemy_id, EnemyPos { x, y });
-    }
+    let enemy_id = commands
+        .spawn((
+            EnemyBundle::new(
+                color,
+                archetype,
+                ms * archetype.speed_scale() * wave_config.speed_scale,
+                enemy_state.elapsed,
+            ),
+            sphere_bundle,
+        ))
+        .insert(Sensor)
+        .insert(Collider::ball(radius))
+        .insert(CollisionGroups::new(Group::GROUP_2, Group::GROUP_1))
+        .id();
+    enemy_state.pos.insert(enemy_id, EnemyPos { x, y });
 }
 
-fn pick_ms(enemy_count: usize) -> f32 {
-    let mut rng = thread_rng();
+fn pick_ms(rng: &mut StdRng, enemy_count: usize) -> f32 {
     let ms_threshold = match enemy_count {
         5 | 6 => 10,
         7 | 8 => rng.gen_range(10..11),
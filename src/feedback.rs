@@ -0,0 +1,204 @@
+use crate::globals::{DamageEvent, GameSpeed, PauseState};
+use crate::player::{player_movement_system, CamMarker, LOOK_CLAMP};
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+const TRAUMA_PER_HIT: f32 = 0.6;
+const TRAUMA_DECAY: f32 = 1.5; // per second
+const MAX_SHAKE_ANGLE: f32 = 0.04;
+const VIGNETTE_PER_HIT: f32 = 0.8;
+const VIGNETTE_DECAY: f32 = 1.2; // per second
+const VIGNETTE_COLOR: Color = Color::srgb(0.8, 0., 0.);
+const HIT_STOP_SECS: f32 = 0.08;
+const HIT_STOP_SPEED: f32 = 0.05;
+
+pub struct DamageFeedbackPlugin;
+
+impl Plugin for DamageFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraShake::default())
+            .insert_resource(DamageVignette(0.))
+            .insert_resource(HitStop(None))
+            .add_systems(Startup, init_vignette)
+            .add_systems(
+                Update,
+                (
+                    on_damage_system,
+                    hit_stop_system,
+                    apply_game_speed_system,
+                    fade_vignette_system,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    remove_shake_system.before(player_movement_system),
+                    apply_shake_system.after(player_movement_system),
+                )
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+    // yaw/pitch offset currently baked into the camera rotation
+    applied: Vec2,
+}
+
+#[derive(Resource)]
+pub struct DamageVignette(pub f32);
+
+#[derive(Resource)]
+struct HitStop(Option<Timer>);
+
+// nested rings that fake a soft edge, outermost first
+#[derive(Component)]
+struct VignetteLayer(f32);
+
+fn on_damage_system(
+    mut damage_observer: EventReader<DamageEvent>,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut vignette: ResMut<DamageVignette>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if damage_observer.read().count() == 0 {
+        return;
+    }
+    let strength = settings.damage_feedback;
+    if strength <= 0. {
+        return;
+    }
+
+    vignette.0 = (vignette.0 + VIGNETTE_PER_HIT * strength).min(1.);
+    if settings.reduced_motion {
+        return;
+    }
+    shake.trauma = (shake.trauma + TRAUMA_PER_HIT * strength).min(1.);
+    time.set_relative_speed(HIT_STOP_SPEED);
+    hit_stop.0 = Some(Timer::from_seconds(
+        HIT_STOP_SECS * strength,
+        TimerMode::Once,
+    ));
+}
+
+fn hit_stop_system(
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    game_speed: Res<GameSpeed>,
+) {
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+    if timer.tick(real_time.delta()).finished() {
+        time.set_relative_speed(game_speed.0);
+        hit_stop.0 = None;
+    }
+}
+
+// hit-stop owns the clock while it runs and hands it back when done
+fn apply_game_speed_system(
+    hit_stop: Res<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+    game_speed: Res<GameSpeed>,
+) {
+    if hit_stop.0.is_none() && game_speed.is_changed() {
+        time.set_relative_speed(game_speed.0);
+    }
+}
+
+fn remove_shake_system(
+    mut shake: ResMut<CameraShake>,
+    mut cam: Query<&mut Transform, With<CamMarker>>,
+) {
+    if shake.applied == Vec2::ZERO {
+        return;
+    }
+    for mut cam in cam.iter_mut() {
+        let (yaw, pitch, roll) = cam.rotation.to_euler(EulerRot::YXZ);
+        cam.rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            yaw - shake.applied.x,
+            pitch - shake.applied.y,
+            roll,
+        );
+    }
+    shake.applied = Vec2::ZERO;
+}
+
+fn apply_shake_system(
+    mut shake: ResMut<CameraShake>,
+    mut cam: Query<&mut Transform, With<CamMarker>>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    if settings.reduced_motion || settings.damage_feedback <= 0. {
+        shake.trauma = 0.;
+    }
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.);
+    if shake.trauma <= 0. {
+        return;
+    }
+
+    let t = time.elapsed_seconds();
+    let amplitude = MAX_SHAKE_ANGLE * shake.trauma * shake.trauma * settings.damage_feedback;
+    let offset = Vec2::new((t * 37.).sin(), (t * 43. + 1.3).sin()) * amplitude;
+
+    for mut cam in cam.iter_mut() {
+        let (yaw, pitch, roll) = cam.rotation.to_euler(EulerRot::YXZ);
+        // shake must never push the view past the look clamps
+        let shaken_yaw = (yaw + offset.x).clamp(-LOOK_CLAMP, LOOK_CLAMP);
+        let shaken_pitch = (pitch + offset.y).clamp(-LOOK_CLAMP, LOOK_CLAMP);
+        cam.rotation = Quat::from_euler(EulerRot::YXZ, shaken_yaw, shaken_pitch, roll);
+        shake.applied = Vec2::new(shaken_yaw - yaw, shaken_pitch - pitch);
+    }
+}
+
+fn fade_vignette_system(
+    mut vignette: ResMut<DamageVignette>,
+    mut layers: Query<(&mut BorderColor, &VignetteLayer)>,
+    time: Res<Time<Real>>,
+) {
+    if vignette.0 <= 0. && !vignette.is_changed() {
+        return;
+    }
+    vignette.0 = (vignette.0 - VIGNETTE_DECAY * time.delta_seconds()).max(0.);
+    for (mut border, layer) in layers.iter_mut() {
+        *border = BorderColor(VIGNETTE_COLOR.with_alpha(vignette.0 * layer.0));
+    }
+}
+
+fn init_vignette(mut commands: Commands) {
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        z_index: ZIndex::Global(5),
+        ..default()
+    };
+    let mut parent = commands.spawn(root).id();
+
+    for alpha in [0.6, 0.35, 0.15] {
+        let ring = NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                border: UiRect::all(Val::Px(24.)),
+                ..default()
+            },
+            border_color: BorderColor(Color::NONE),
+            ..default()
+        };
+        let ring = commands.spawn((ring, VignetteLayer(alpha))).id();
+        commands.entity(parent).push_children(&[ring]);
+        parent = ring;
+    }
+}
@@ -0,0 +1,163 @@
+use crate::globals::Kulay;
+use crate::input::{update_pointer_system, TrackerMessage};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct GesturePlugin;
+
+impl Plugin for GesturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Gestures::default())
+            .add_event::<GestureAction>()
+            .add_systems(PreUpdate, gesture_system.after(update_pointer_system));
+    }
+}
+
+// what the game does with a gesture, once it has settled
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum GestureAction {
+    // pinch closed
+    Fire,
+    // pinch opened again, charge weapons let go on this
+    CeaseFire,
+    // an open hand picks blue, a closed fist red, and a fist held on picks
+    // yellow for runs that have it
+    SelectWeapon(Kulay),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GestureConfig {
+    // pinch distances, in the tracker's 0..1 image units; the gap between the
+    // two keeps a pinch hovering near the line from chattering
+    pub pinch_press: f32,
+    pub pinch_release: f32,
+    // seconds a new reading has to hold before it counts
+    pub pinch_debounce: f32,
+    pub fist_debounce: f32,
+    // seconds a settled fist has to stay closed to pick the third color
+    pub fist_hold: f32,
+    // seconds without a reading before the hand counts as lost and lets go
+    pub hand_timeout: f32,
+}
+
+// settled gesture states, as of the last tracker message
+#[derive(Resource, Default)]
+pub struct Gestures {
+    pub pinch: Debounced,
+    pub fist: Debounced,
+    // the fist that's closed now has already been held long enough for yellow
+    fist_held: bool,
+}
+
+#[derive(Default)]
+pub struct Debounced {
+    pub on: bool,
+    // real seconds the reading has disagreed with `on` since
+    changing_since: Option<f32>,
+    // real seconds `on` last changed at
+    settled_at: f32,
+    last_reading: Option<f32>,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            pinch_press: 0.04,
+            pinch_release: 0.06,
+            pinch_debounce: 0.05,
+            fist_debounce: 0.2,
+            fist_hold: 0.8,
+            hand_timeout: 0.25,
+        }
+    }
+}
+
+impl Debounced {
+    // the new state, when this reading settles a change
+    fn update(&mut self, reading: bool, now: f32, hold: f32) -> Option<bool> {
+        self.last_reading = Some(now);
+        if reading == self.on {
+            self.changing_since = None;
+            return None;
+        }
+        let since = *self.changing_since.get_or_insert(now);
+        if now - since < hold {
+            return None;
+        }
+        self.on = reading;
+        self.changing_since = None;
+        self.settled_at = now;
+        Some(reading)
+    }
+
+    // how long the gesture has been held, if it is
+    fn held_for(&self, now: f32) -> Option<f32> {
+        self.on.then_some(now - self.settled_at)
+    }
+
+    // lets go once the readings stop, which is how the tracker loses a hand;
+    // true when that let go of a held gesture
+    fn expire(&mut self, now: f32, timeout: f32) -> bool {
+        let lost = self.last_reading.is_some_and(|last| now - last >= timeout);
+        if !lost || !self.on {
+            return false;
+        }
+        self.on = false;
+        self.changing_since = None;
+        true
+    }
+}
+
+pub fn gesture_system(
+    mut gestures: ResMut<Gestures>,
+    mut messages: EventReader<TrackerMessage>,
+    mut actions: EventWriter<GestureAction>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let config = &settings.gestures;
+    let now = time.elapsed_seconds();
+    for message in messages.read() {
+        match *message {
+            TrackerMessage::Pinch(distance) => {
+                let threshold = if gestures.pinch.on {
+                    config.pinch_release
+                } else {
+                    config.pinch_press
+                };
+                match gestures
+                    .pinch
+                    .update(distance < threshold, now, config.pinch_debounce)
+                {
+                    Some(true) => actions.send(GestureAction::Fire),
+                    Some(false) => actions.send(GestureAction::CeaseFire),
+                    None => continue,
+                };
+            }
+            TrackerMessage::Fist(closed) => {
+                let Some(closed) = gestures.fist.update(closed, now, config.fist_debounce) else {
+                    continue;
+                };
+                let color = if closed { Kulay::Pula } else { Kulay::Asul };
+                actions.send(GestureAction::SelectWeapon(color));
+                gestures.fist_held = false;
+            }
+            TrackerMessage::Aim(_) => {}
+        }
+    }
+
+    // a pinch the tracker lost track of would otherwise hold the trigger for good
+    if gestures.pinch.expire(now, config.hand_timeout) {
+        actions.send(GestureAction::CeaseFire);
+    }
+    // the weapon keeps its color, the fist just reads as open until the hand is back
+    gestures.fist.expire(now, config.hand_timeout);
+
+    let held = gestures.fist.held_for(now).unwrap_or(0.);
+    if !gestures.fist_held && held >= config.fist_hold {
+        gestures.fist_held = true;
+        actions.send(GestureAction::SelectWeapon(Kulay::Dilaw));
+    }
+}
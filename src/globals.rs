@@ -1,13 +1,32 @@
+use crate::audio::{PlaySfxAt, SfxId};
+use crate::daily::DailyRun;
 use crate::enemy::{eliminate_enemy, Enemy, EnemyState};
-use crate::hud::{clean_hud_system, HudEntities, Score};
-use crate::player::{KillCount, PlayerHealth, PlayerMarker};
+use crate::hud::{
+    clean_hud_system, Combo, HudEntities, PlayerScores, Score, ScorePopupEvent, Scores,
+};
+use crate::mode::{GameMode, RoundTimer, DAMAGE_PENALTY};
+use crate::player::{KillCount, MaxHealth, PlayerHealth, PlayerMarker, PlayerShield, PlayerWeapon};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-#[derive(Component, Debug, PartialEq)]
+#[derive(Component, Debug, PartialEq, Clone, Copy)]
 pub enum Kulay {
     Pula,
     Asul,
+    // only shows up when a run asks for three colors
+    Dilaw,
+}
+
+impl Kulay {
+    pub const ALL: [Kulay; 3] = [Kulay::Pula, Kulay::Asul, Kulay::Dilaw];
+
+    pub fn color(&self) -> Color {
+        match self {
+            Kulay::Pula => Color::hsl(0., 0.5, 0.5),
+            Kulay::Asul => Color::hsl(240., 0.8, 0.5),
+            Kulay::Dilaw => Color::hsl(50., 0.9, 0.5),
+        }
+    }
 }
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
@@ -17,28 +36,46 @@ pub enum GameState {
     GameOver,
 }
 
+// only exists while in game, so gameplay systems can keep using `in_state(GameState::InGame)`
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameState = GameState::InGame)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
 #[derive(Event)]
 pub struct DamageEvent;
 
+// how fast virtual time runs outside of hit-stop, lowered by slow motion
+#[derive(Resource)]
+pub struct GameSpeed(pub f32);
+
 pub struct Global;
 
 impl Plugin for Global {
     fn build(&self, app: &mut App) {
         app.add_event::<DamageEvent>()
+            .insert_resource(GameSpeed(1.))
             .add_systems(Update, player_enemy_collider_system)
+            // quitting from the pause menu leaves the run behind
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (clean_hud_system, reset_system).chain(),
+            )
             .add_systems(
                 Update,
-                (clean_hud_system, update_game_state_to_ingame)
-                    .run_if(mouse_pressed_and_not_ingame),
+                (clean_hud_system, update_game_state_to_ingame).run_if(mouse_pressed_and_gameover),
             );
     }
 }
 
-fn mouse_pressed_and_not_ingame(
+fn mouse_pressed_and_gameover(
     game_state: Res<State<GameState>>,
     input: Res<ButtonInput<MouseButton>>,
 ) -> bool {
-    *game_state.get() != GameState::InGame && input.just_pressed(MouseButton::Left)
+    *game_state.get() == GameState::GameOver && input.just_pressed(MouseButton::Left)
 }
 
 fn update_game_state_to_ingame(
@@ -53,14 +90,18 @@ fn update_game_state_to_ingame(
 pub fn player_enemy_collider_system(
     mut commands: Commands,
     player_collider: Query<Entity, With<PlayerMarker>>,
-    enemies: Query<Entity, With<Enemy>>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
     rapier_context: Res<RapierContext>,
     mut player_health: ResMut<PlayerHealth>,
+    mut shield: ResMut<PlayerShield>,
     mut enemy_state: ResMut<EnemyState>,
     mut damage_event: EventWriter<DamageEvent>,
     mut next_state: ResMut<NextState<GameState>>,
     game_state: Res<State<GameState>>,
-    asset_server: Res<AssetServer>
+    mut play_sfx: EventWriter<PlaySfxAt>,
+    mode: Res<GameMode>,
+    mut scores: Scores,
+    mut score_popup: EventWriter<ScorePopupEvent>,
 ) {
     if player_health.0 == 0 && *game_state.get() != GameState::GameOver {
         next_state.set(GameState::GameOver);
@@ -68,16 +109,30 @@ pub fn player_enemy_collider_system(
     }
 
     let player = player_collider.single();
-    for enemy in &enemies {
+    for (enemy, enemy_transform) in &enemies {
         // TEMP FIX
         if rapier_context.intersection_pair(player, enemy).is_some() {
-            if player_health.0 != 0 {
-                player_health.0 -= 1;
+            if mode.damage_free() {
+                eliminate_enemy(&mut commands, enemy, &mut enemy_state);
+                continue;
+            }
+            if shield.0 != 0 {
+                shield.0 -= 1;
                 damage_event.send(DamageEvent);
-                commands.spawn(AudioBundle {
-                    source: asset_server.load("rizz.ogg"),
-                    settings: PlaybackSettings::default(),
+                play_sfx.send(PlaySfxAt(SfxId::Damage, enemy_transform.translation()));
+            } else if mode.unlimited_health() {
+                scores.add_shared(mode.players(), -DAMAGE_PENALTY);
+                damage_event.send(DamageEvent);
+                play_sfx.send(PlaySfxAt(SfxId::PlayerHit, enemy_transform.translation()));
+                score_popup.send(ScorePopupEvent {
+                    points: -DAMAGE_PENALTY,
+                    multiplier: 1,
+                    pos: Some(enemy_transform.translation()),
                 });
+            } else if player_health.0 != 0 {
+                player_health.0 -= 1;
+                damage_event.send(DamageEvent);
+                play_sfx.send(PlaySfxAt(SfxId::PlayerHit, enemy_transform.translation()));
             }
             eliminate_enemy(&mut commands, enemy, &mut enemy_state);
         }
@@ -91,17 +146,35 @@ pub fn reset_system(
     enemies: Query<Entity, With<Enemy>>,
     mut enemy_state: ResMut<EnemyState>,
     mut score: ResMut<Score>,
+    mut player_scores: ResMut<PlayerScores>,
+    mut combo: ResMut<Combo>,
     mut hud_entities: ResMut<HudEntities>,
     mut player_health: ResMut<PlayerHealth>,
+    mut max_health: ResMut<MaxHealth>,
+    mut shield: ResMut<PlayerShield>,
     mut kill_count: ResMut<KillCount>,
+    mut weapon: Query<&mut PlayerWeapon>,
+    mode: Res<GameMode>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut daily_run: ResMut<DailyRun>,
 ) {
     for enemy in &enemies {
         eliminate_enemy(&mut commands, enemy, &mut enemy_state);
     }
 
     score.0 = 0;
+    *player_scores = PlayerScores::default();
+    combo.streak = 0;
     hud_entities.0.clear();
-    player_health.0 = 5;
+    player_health.0 = mode.starting_health();
+    max_health.0 = mode.starting_health();
+    shield.0 = 0;
     kill_count.0 = 0;
+    *round_timer = RoundTimer::for_mode(*mode);
+    for mut weapon in weapon.iter_mut() {
+        weapon.refill();
+    }
     *enemy_state = EnemyState::default();
+    // a reset mid-run carries on as practice, the official attempt is already spent
+    daily_run.official = false;
 }
@@ -1,17 +1,46 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
 
 use crate::{
-    globals::{DamageEvent, GameState, Kulay},
-    player::{PlayerHealth, PlayerWeapon},
+    aim::Aim,
+    coop::SecondPlayer,
+    daily::DailyRun,
+    enemy::{Enemy, MovementSpeed},
+    globals::{GameState, Kulay},
+    mode::GameMode,
+    player::{CamMarker, MaxHealth, PlayerHealth, PlayerShield, PlayerWeapon, Shooter},
+    save::Progress,
+    settings::Settings,
 };
 
+const POPUP_POOL_SIZE: usize = 16;
+const POPUP_LIFETIME: f32 = 0.8;
+// px the popup rises over its lifetime
+const POPUP_RISE: f32 = 60.;
+// every this many kills in a row bumps the score multiplier
+const COMBO_STEP: u32 = 5;
+const MAX_COMBO_MULTIPLIER: u32 = 4;
+const HUD_POP_SECS: f32 = 0.3;
+const SHIELD_COLOR: Color = Color::srgb(0.3, 0.8, 1.);
+const RADAR_SIZE: f32 = 160.;
+// world units from the camera to the radar's edge
+const RADAR_RANGE: f32 = 60.;
+const RADAR_BLIP_SIZE: f32 = 8.;
+// blips are drawn at RADAR_BLIP_SIZE for enemies moving this fast
+const RADAR_BASE_SPEED: f32 = 8.;
+const RADAR_POOL_SIZE: usize = 16;
+
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Score(0))
-            .insert_resource(HealthBarState(Vec::new()))
-            .insert_resource(HudEntities(Vec::new()));
+            .insert_resource(PlayerScores::default())
+            .insert_resource(Combo::default())
+            .insert_resource(HudEntities(Vec::new()))
+            .insert_resource(PopupPool(Vec::new()))
+            .insert_resource(RadarBlips(Vec::new()))
+            .add_event::<ScorePopupEvent>();
 
         app.add_systems(
             OnEnter(GameState::InGame),
@@ -20,6 +49,8 @@ impl Plugin for HudPlugin {
                 init_crosshair_ui_system,
                 init_scoreboard_system,
                 init_healthbar_hud,
+                init_popup_pool,
+                init_radar_hud,
             )
                 .chain(),
         )
@@ -28,7 +59,10 @@ impl Plugin for HudPlugin {
             (
                 refresh_scoreboard_system,
                 refresh_crosshair_color_system,
-                animate_health_deplete_system,
+                refresh_ammo_system,
+                (refresh_health_widget_system, animate_hud_pop_system).chain(),
+                (spawn_score_popup_system, animate_score_popup_system).chain(),
+                refresh_radar_system,
             )
                 .run_if(in_state(GameState::InGame)),
         )
@@ -46,26 +80,158 @@ pub struct ScoreBoardMarker;
 #[derive(Resource)]
 pub struct Score(pub i32);
 
+// each player's share of `Score`, indexed by `Shooter::index`
+#[derive(Resource, Default)]
+pub struct PlayerScores(pub [i32; 2]);
+
+// the team score and each player's share of it, kept in step
+#[derive(SystemParam)]
+pub struct Scores<'w> {
+    total: ResMut<'w, Score>,
+    players: ResMut<'w, PlayerScores>,
+}
+
 #[derive(Component)]
 pub struct CrossHairMarker;
 
 #[derive(Component)]
-struct HealthDepleteMarker;
+struct AmmoMarker;
 
-#[derive(Resource)]
-struct HealthBarState(pub Vec<Entity>);
+#[derive(Component)]
+struct HeartRow;
+
+#[derive(Component)]
+struct ShieldRow;
+
+#[derive(Component)]
+struct HeartSlot {
+    index: usize,
+    filled: bool,
+}
+
+#[derive(Component)]
+struct ShieldSlot(usize);
+
+// scales a hud node from `from` back to 1, or down to 0 and despawns it
+#[derive(Component)]
+struct HudPop {
+    timer: Timer,
+    from: f32,
+    despawn: bool,
+}
 
 #[derive(Resource)]
 pub struct HudEntities(pub Vec<Entity>);
 
+#[derive(Resource, Default)]
+pub struct Combo {
+    pub streak: u32,
+}
+
+// world position floats the popup over that spot, otherwise over the crosshair
+#[derive(Event)]
+pub struct ScorePopupEvent {
+    pub points: i32,
+    pub multiplier: u32,
+    pub pos: Option<Vec3>,
+}
+
+#[derive(Component)]
+struct ScorePopup {
+    lifetime: Timer,
+    anchor: Vec2,
+    color: Color,
+}
+
+// text nodes reused for popups, rebuilt along with the rest of the hud
+#[derive(Resource)]
+struct PopupPool(Vec<Entity>);
+
+#[derive(Component)]
+struct RadarMarker;
+
+// dots reused for enemies on the radar, rebuilt along with the rest of the hud
+#[derive(Resource)]
+struct RadarBlips(Vec<Entity>);
+
+impl HudPop {
+    fn new(from: f32, despawn: bool) -> Self {
+        Self {
+            timer: Timer::from_seconds(HUD_POP_SECS, TimerMode::Once),
+            from,
+            despawn,
+        }
+    }
+
+    // grows in from nothing
+    fn grow() -> Self {
+        Self::new(0., false)
+    }
+
+    // swells and settles, for a heart that was just lost
+    fn punch() -> Self {
+        Self::new(1.6, false)
+    }
+
+    fn shrink() -> Self {
+        Self::new(1., true)
+    }
+}
+
+impl Scores<'_> {
+    pub fn add(&mut self, shooter: Shooter, points: i32) {
+        self.total.0 += points;
+        self.players.0[shooter.index()] += points;
+    }
+
+    // for points nobody in particular earned, split across everyone playing
+    pub fn add_shared(&mut self, players: usize, points: i32) {
+        let share = points / players as i32;
+        for shooter in &Shooter::ALL[..players] {
+            self.add(*shooter, share);
+        }
+        // whatever doesn't divide evenly goes to player one
+        self.add(Shooter::One, points - share * players as i32);
+    }
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / COMBO_STEP).min(MAX_COMBO_MULTIPLIER)
+    }
+}
+
+// None when the point is behind the camera
+pub fn world_to_viewport(cam: &Camera, cam_transform: &GlobalTransform, pos: Vec3) -> Option<Vec2> {
+    let to_point = pos - cam_transform.translation();
+    if to_point.dot(*cam_transform.forward()) <= 0. {
+        return None;
+    }
+    cam.world_to_viewport(cam_transform, pos)
+}
+
+fn scoreboard_text(score: &Score, combo: &Combo, players: &PlayerScores, mode: GameMode) -> String {
+    let total = match combo.multiplier() {
+        1 => format!("Score: {}", score.0),
+        multiplier => format!("Score: {}  x{}", score.0, multiplier),
+    };
+    match mode.players() {
+        1 => total,
+        _ => format!("{}\nP1: {}  P2: {}", total, players.0[0], players.0[1]),
+    }
+}
+
 // rename these shets
 pub fn refresh_scoreboard_system(
     scoreboard_points: Res<Score>,
+    combo: Res<Combo>,
+    player_scores: Res<PlayerScores>,
+    mode: Res<GameMode>,
     mut scoreboard_ui: Query<&mut Text, With<ScoreBoardMarker>>,
 ) {
     for mut score_ui in scoreboard_ui.iter_mut() {
         *score_ui = Text::from_section(
-            format!("Score: {}", scoreboard_points.0),
+            scoreboard_text(&scoreboard_points, &combo, &player_scores, *mode),
             TextStyle {
                 font_size: 50.,
                 ..default()
@@ -85,56 +251,22 @@ pub fn clean_hud_system(mut commands: Commands, mut hud_entities: ResMut<HudEnti
 }
 
 fn refresh_crosshair_color_system(
-    mut crosshair: Query<&mut BorderColor, With<CrossHairMarker>>,
-    player_weapon: Res<PlayerWeapon>,
+    mut crosshair: Query<(&mut BorderColor, &Shooter), With<CrossHairMarker>>,
+    player_weapon: Query<(&PlayerWeapon, &Shooter)>,
 ) {
-    for mut crosshair in crosshair.iter_mut() {
-        *crosshair = match player_weapon.0 {
+    for (mut crosshair, shooter) in crosshair.iter_mut() {
+        let Some((player_weapon, _)) = player_weapon.iter().find(|(_, s)| *s == shooter) else {
+            continue;
+        };
+        *crosshair = match player_weapon.color {
             Kulay::Asul => BorderColor(Color::hsl(197.4, 0.714, 0.575)),
             Kulay::Pula => BorderColor(Color::hsl(0., 0.5, 0.5)),
+            Kulay::Dilaw => BorderColor(Kulay::Dilaw.color()),
         };
     }
 }
 
-fn animate_health_deplete_system(
-    mut commands: Commands,
-    mut query: Query<(&mut Transform, Entity), With<HealthDepleteMarker>>,
-    mut damage_observer: EventReader<DamageEvent>,
-    mut health_bar_state: ResMut<HealthBarState>,
-    time: Res<Time>,
-    mut hearts_to_animate: Local<Vec<Entity>>,
-) {
-    for _ in damage_observer.read() {
-        let Some(health_bar_entity) = health_bar_state.0.pop() else {
-            return;
-        };
-        let Ok((_, entity)) = query.get(health_bar_entity) else {
-            return;
-        };
-        hearts_to_animate.push(entity);
-    }
-
-    hearts_to_animate.retain_mut(|health_bar_entity| {
-        let Ok((mut transform, entity)) = query.get_mut(*health_bar_entity) else {
-            return false;
-        };
-        if transform.scale.x >= 0. {
-            transform.scale -= Vec3::splat(1.) * time.delta_seconds();
-        } else {
-            commands.entity(entity).despawn(); // should also despawn its parent
-            return false;
-        }
-        true
-    });
-}
-
-fn init_healthbar_hud(
-    mut commands: Commands,
-    player_health: Res<PlayerHealth>,
-    asset_server: Res<AssetServer>,
-    mut health_bar_state: ResMut<HealthBarState>,
-    mut hud_entities: ResMut<HudEntities>,
-) {
+fn init_healthbar_hud(mut commands: Commands, mut hud_entities: ResMut<HudEntities>) {
     let container = NodeBundle {
         style: Style {
             width: Val::Percent(100.),
@@ -150,31 +282,143 @@ fn init_healthbar_hud(
     let container = commands.spawn(container).id();
     hud_entities.0.push(container);
 
-    let heart = UiImage::new(asset_server.load("pixel_heart.png")); // change img
-    let mut stack: Vec<Entity> = vec![];
-    for _ in 0..player_health.0 {
-        let heart_container = NodeBundle {
-            style: Style {
-                width: Val::Px(32.),
-                height: Val::Px(32.),
-                margin: UiRect::all(Val::Px(5.)),
-                ..default()
-            },
-            ..default()
+    // hearts and shields get their own rows so new slots always append in order
+    let hearts = commands.spawn((NodeBundle::default(), HeartRow)).id();
+    let shields = commands.spawn((NodeBundle::default(), ShieldRow)).id();
+    commands.entity(container).push_children(&[hearts, shields]);
+}
+
+// brings the heart and shield slots in line with the player's current state,
+// only when that state changed or the widget was just rebuilt
+fn refresh_health_widget_system(
+    mut commands: Commands,
+    player_health: Res<PlayerHealth>,
+    max_health: Res<MaxHealth>,
+    shield: Res<PlayerShield>,
+    heart_row: Query<(Entity, Ref<HeartRow>)>,
+    shield_row: Query<Entity, With<ShieldRow>>,
+    mut hearts: Query<(Entity, &mut HeartSlot, &mut UiImage, Option<&HudPop>)>,
+    shields: Query<(Entity, &ShieldSlot, Option<&HudPop>)>,
+    asset_server: Res<AssetServer>,
+) {
+    let (Ok((heart_row, heart_row_ref)), Ok(shield_row)) =
+        (heart_row.get_single(), shield_row.get_single())
+    else {
+        return;
+    };
+    let rebuilt = heart_row_ref.is_added();
+    if !rebuilt && !player_health.is_changed() && !max_health.is_changed() && !shield.is_changed() {
+        return;
+    }
+
+    let mut heart_count = 0;
+    for (entity, mut slot, mut image, pop) in hearts.iter_mut() {
+        if pop.is_some_and(|pop| pop.despawn) {
+            continue;
+        }
+        if slot.index >= max_health.0 {
+            commands.entity(entity).insert(HudPop::shrink());
+            continue;
+        }
+        heart_count += 1;
+        let filled = slot.index < player_health.0;
+        if filled == slot.filled {
+            continue;
+        }
+        slot.filled = filled;
+        image.color = heart_tint(filled);
+        let pop = if filled {
+            HudPop::grow()
+        } else {
+            HudPop::punch()
         };
-        let heart_hud = commands
-            .spawn((heart_container, heart.clone(), HealthDepleteMarker))
+        commands.entity(entity).insert(pop);
+    }
+
+    let heart = asset_server.load("pixel_heart.png"); // change img
+    for index in heart_count..max_health.0 {
+        let filled = index < player_health.0;
+        let slot = commands
+            .spawn((
+                heart_slot_bundle(),
+                UiImage::new(heart.clone()).with_color(heart_tint(filled)),
+                HeartSlot { index, filled },
+                HudPop::grow(),
+            ))
             .id();
-        commands.entity(container).push_children(&[heart_hud]);
-        stack.push(heart_hud);
+        commands.entity(heart_row).add_child(slot);
     }
 
-    health_bar_state.0 = stack;
+    let mut shield_slots: Vec<(Entity, usize)> = shields
+        .iter()
+        .filter(|(_, _, pop)| !pop.is_some_and(|pop| pop.despawn))
+        .map(|(entity, slot, _)| (entity, slot.0))
+        .collect();
+    shield_slots.sort_by_key(|(_, index)| *index);
+    for &(entity, _) in shield_slots.iter().skip(shield.0) {
+        commands.entity(entity).insert(HudPop::shrink());
+    }
+    for index in shield_slots.len()..shield.0 {
+        let slot = NodeBundle {
+            border_color: BorderColor(SHIELD_COLOR),
+            border_radius: BorderRadius::all(Val::Percent(50.)),
+            ..heart_slot_bundle()
+        };
+        let slot = commands
+            .spawn((slot, ShieldSlot(index), HudPop::grow()))
+            .id();
+        commands.entity(shield_row).add_child(slot);
+    }
+}
+
+fn animate_hud_pop_system(
+    mut commands: Commands,
+    mut pops: Query<(Entity, &mut HudPop, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut pop, mut transform) in pops.iter_mut() {
+        pop.timer.tick(time.delta());
+        let target = if pop.despawn { 0. } else { 1. };
+        let scale = pop.from + (target - pop.from) * pop.timer.fraction();
+        transform.scale = Vec3::splat(scale);
+        if !pop.timer.finished() {
+            continue;
+        }
+        if pop.despawn {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            commands.entity(entity).remove::<HudPop>();
+        }
+    }
+}
+
+fn heart_slot_bundle() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Px(32.),
+            height: Val::Px(32.),
+            margin: UiRect::all(Val::Px(5.)),
+            border: UiRect::all(Val::Px(4.)),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn heart_tint(filled: bool) -> Color {
+    if filled {
+        Color::WHITE
+    } else {
+        Color::srgba(0.2, 0.2, 0.2, 0.5)
+    }
 }
 
 fn init_scoreboard_system(
     mut commands: Commands,
     score_board: Res<Score>,
+    combo: Res<Combo>,
+    player_scores: Res<PlayerScores>,
+    mode: Res<GameMode>,
     mut hud_entities: ResMut<HudEntities>,
 ) {
     let scoreboard_ui = NodeBundle {
@@ -193,7 +437,7 @@ fn init_scoreboard_system(
     hud_entities.0.push(scoreboard);
 
     let text_bundle = TextBundle::from_section(
-        format!("Score: {}", score_board.0),
+        scoreboard_text(&score_board, &combo, &player_scores, *mode),
         TextStyle {
             font_size: 50.,
             ..default()
@@ -221,18 +465,27 @@ fn init_scoreboard_system(
 }
 
 fn update_crosshair_pos(
-    win: Query<&Window, With<PrimaryWindow>>,
-    mut crosshair: Query<&mut Style, With<CrossHairMarker>>,
+    aim: Res<Aim>,
+    second: Res<SecondPlayer>,
+    mut crosshair: Query<(&mut Style, &Shooter), With<CrossHairMarker>>,
 ) {
-    for mut crosshair in crosshair.iter_mut() {
-        if let Some(pos) = win.single().cursor_position() {
+    for (mut crosshair, shooter) in crosshair.iter_mut() {
+        let pos = match shooter {
+            Shooter::One => aim.pos,
+            Shooter::Two => second.pos,
+        };
+        if let Some(pos) = pos {
             crosshair.left = Val::Px(pos.x - 10.);
             crosshair.top = Val::Px(pos.y - 20.);
         }
     }
 }
 
-fn init_crosshair_ui_system(mut commands: Commands, mut hud_entities: ResMut<HudEntities>) {
+fn init_crosshair_ui_system(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
     let ui_screen = NodeBundle {
         style: Style {
             position_type: PositionType::Absolute,
@@ -245,26 +498,92 @@ fn init_crosshair_ui_system(mut commands: Commands, mut hud_entities: ResMut<Hud
     let ui_entity = commands.spawn(ui_screen).id();
     hud_entities.0.push(ui_entity);
 
-    let crosshair_bundle = NodeBundle {
-        style: Style {
-            height: Val::Px(35.),
-            width: Val::Px(35.),
-            border: UiRect::all(Val::Px(5.)),
+    for shooter in &Shooter::ALL[..mode.players()] {
+        // player two's is square so the two can be told apart at a glance
+        let radius = match shooter {
+            Shooter::One => Val::Percent(50.),
+            Shooter::Two => Val::Px(4.),
+        };
+        let crosshair_bundle = NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                height: Val::Px(35.),
+                width: Val::Px(35.),
+                border: UiRect::all(Val::Px(5.)),
+                ..default()
+            },
+            border_radius: BorderRadius::all(radius),
+            border_color: BorderColor(Color::hsl(0., 0.5, 0.5)),
             ..default()
-        },
-        border_radius: BorderRadius::all(Val::Percent(50.)),
-        border_color: BorderColor(Color::hsl(0., 0.5, 0.5)),
-        ..default()
+        };
+        let crosshair_entity = commands
+            .spawn((CrossHairMarker, crosshair_bundle, *shooter))
+            .id();
+        commands
+            .entity(ui_entity)
+            .push_children(&[crosshair_entity]);
+
+        // rides along with the crosshair, just off to its right
+        let ammo = TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(40.),
+                top: Val::Px(5.),
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.,
+                    ..default()
+                },
+            ),
+            ..default()
+        };
+        let ammo = commands.spawn((ammo, AmmoMarker, *shooter)).id();
+        commands.entity(crosshair_entity).add_child(ammo);
+    }
+}
+
+fn refresh_ammo_system(
+    player_weapon: Query<(&PlayerWeapon, &Shooter), Changed<PlayerWeapon>>,
+    mut ammo_ui: Query<(&mut Text, &Shooter), With<AmmoMarker>>,
+) {
+    for (weapon, shooter) in player_weapon.iter() {
+        let text = ammo_text(weapon);
+        for (mut ammo, _) in ammo_ui.iter_mut().filter(|(_, s)| *s == shooter) {
+            ammo.sections[0].value = text.clone();
+        }
+    }
+}
+
+fn ammo_text(weapon: &PlayerWeapon) -> String {
+    let spec = weapon.spec();
+    let status = if weapon.reloading > 0. {
+        format!("reloading {:.1}s", weapon.reloading)
+    } else if let Some(charge_secs) = spec.charge.filter(|_| weapon.charge > 0.) {
+        format!(
+            "charge {:.0}%",
+            (weapon.charge / charge_secs).min(1.) * 100.
+        )
+    } else {
+        format!("{}/{}", weapon.ammo, spec.magazine)
     };
-    let crosshair_entity = commands.spawn((CrossHairMarker, crosshair_bundle)).id();
-    commands
-        .entity(ui_entity)
-        .push_children(&[crosshair_entity]);
+    let lock = if weapon.switch_lock > 0. {
+        format!("\ncolor locked {:.1}s", weapon.switch_lock)
+    } else {
+        String::new()
+    };
+    format!("{}\n{}{}", spec.name, status, lock)
 }
 
 fn init_gameover_screen(
     mut commands: Commands,
     score: Res<Score>,
+    player_scores: Res<PlayerScores>,
+    mode: Res<GameMode>,
+    progress: Res<Progress>,
+    daily_run: Res<DailyRun>,
     mut hud_entities: ResMut<HudEntities>,
 ) {
     let screen = NodeBundle {
@@ -284,9 +603,16 @@ fn init_gameover_screen(
     let screen = commands.spawn(screen).id();
     hud_entities.0.push(screen);
 
+    let scored = match mode.players() {
+        1 => format!("You Scored: {}", score.0),
+        _ => format!(
+            "You Scored: {}\nP1: {}  P2: {}",
+            score.0, player_scores.0[0], player_scores.0[1]
+        ),
+    };
     let text_score = TextBundle {
         text: Text::from_section(
-            format!("You Scored: {}", score.0),
+            scored,
             TextStyle {
                 font_size: 64.,
                 ..default()
@@ -296,6 +622,25 @@ fn init_gameover_screen(
     };
     let text_score = commands.spawn(text_score).id();
 
+    // the run may or may not be on the leaderboard yet, so count it either way
+    let best = progress
+        .high_scores(*mode)
+        .first()
+        .map_or(score.0, |best| score.0.max(*best));
+    let label = match &daily_run.challenge {
+        Some(challenge) if daily_run.official => format!("Daily {}", challenge.date()),
+        Some(challenge) => format!("Daily {} (practice)", challenge.date()),
+        None => mode.label().to_string(),
+    };
+    let text_mode = TextBundle::from_section(
+        format!("{}  |  Best: {}", label, best),
+        TextStyle {
+            font_size: 32.,
+            ..default()
+        },
+    );
+    let text_mode = commands.spawn(text_mode).id();
+
     // let's hope na di na kailangan ng node bundle kapag maglalagay ng text
     let text_label = TextBundle {
         style: Style {
@@ -315,5 +660,221 @@ fn init_gameover_screen(
 
     commands
         .entity(screen)
-        .push_children(&[text_score, text_label]);
+        .push_children(&[text_score, text_mode, text_label]);
+}
+
+fn init_popup_pool(
+    mut commands: Commands,
+    mut pool: ResMut<PopupPool>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
+    pool.0.clear();
+    for _ in 0..POPUP_POOL_SIZE {
+        let popup = TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 32.,
+                    ..default()
+                },
+            ),
+            visibility: Visibility::Hidden,
+            ..default()
+        };
+        let popup = commands.spawn(popup).id();
+        hud_entities.0.push(popup);
+        pool.0.push(popup);
+    }
+}
+
+fn spawn_score_popup_system(
+    mut commands: Commands,
+    mut popup_events: EventReader<ScorePopupEvent>,
+    mut pool: ResMut<PopupPool>,
+    mut popups: Query<(&mut Text, &mut Visibility)>,
+    cam: Query<(&GlobalTransform, &Camera), With<CamMarker>>,
+    aim: Res<Aim>,
+) {
+    let Ok((cam_transform, cam)) = cam.get_single() else {
+        return;
+    };
+    let crosshair = aim.pos;
+
+    for popup_event in popup_events.read() {
+        let anchor = popup_event
+            .pos
+            .and_then(|pos| world_to_viewport(cam, cam_transform, pos))
+            .or(crosshair);
+        let Some(anchor) = anchor else {
+            continue;
+        };
+
+        // the front of the pool is the popup that has been up the longest
+        let Some(&entity) = pool.0.first() else {
+            return;
+        };
+        pool.0.rotate_left(1);
+        let Ok((mut text, mut visibility)) = popups.get_mut(entity) else {
+            continue;
+        };
+
+        let color = if popup_event.points >= 0 {
+            Color::srgb(1., 0.9, 0.3)
+        } else {
+            Color::srgb(1., 0.3, 0.3)
+        };
+        let label = match popup_event.multiplier {
+            0 | 1 => format!("{:+}", popup_event.points),
+            multiplier => format!("{:+} x{}", popup_event.points, multiplier),
+        };
+        text.sections[0].value = label;
+        text.sections[0].style.color = color;
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).insert(ScorePopup {
+            lifetime: Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once),
+            anchor,
+            color,
+        });
+    }
+}
+
+fn animate_score_popup_system(
+    mut commands: Commands,
+    mut popups: Query<(
+        Entity,
+        &mut ScorePopup,
+        &mut Style,
+        &mut Text,
+        &mut Visibility,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut style, mut text, mut visibility) in popups.iter_mut() {
+        popup.lifetime.tick(time.delta());
+        if popup.lifetime.finished() {
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).remove::<ScorePopup>();
+            continue;
+        }
+        let progress = popup.lifetime.fraction();
+        style.left = Val::Px(popup.anchor.x - 30.);
+        style.top = Val::Px(popup.anchor.y - 40. - POPUP_RISE * progress);
+        text.sections[0].style.color = popup.color.with_alpha(1. - progress);
+    }
+}
+
+fn init_radar_hud(
+    mut commands: Commands,
+    mut blips: ResMut<RadarBlips>,
+    mut hud_entities: ResMut<HudEntities>,
+    settings: Res<Settings>,
+) {
+    let radar = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(50.),
+            bottom: Val::Px(50.),
+            width: Val::Px(RADAR_SIZE),
+            height: Val::Px(RADAR_SIZE),
+            border: UiRect::all(Val::Px(2.)),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        border_radius: BorderRadius::all(Val::Percent(50.)),
+        border_color: BorderColor(Color::srgba(1., 1., 1., 0.4)),
+        background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.4)),
+        visibility: if settings.show_radar {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        ..default()
+    };
+    let radar = commands.spawn((radar, RadarMarker)).id();
+    hud_entities.0.push(radar);
+
+    // the player sits in the middle, facing up
+    let player = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(RADAR_SIZE / 2. - 4.),
+            top: Val::Px(RADAR_SIZE / 2. - 4.),
+            width: Val::Px(4.),
+            height: Val::Px(4.),
+            ..default()
+        },
+        background_color: BackgroundColor(Color::WHITE),
+        ..default()
+    };
+    let player = commands.spawn(player).id();
+    commands.entity(radar).add_child(player);
+
+    blips.0.clear();
+    for _ in 0..RADAR_POOL_SIZE {
+        let blip = NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            border_radius: BorderRadius::all(Val::Percent(50.)),
+            visibility: Visibility::Hidden,
+            ..default()
+        };
+        let blip = commands.spawn(blip).id();
+        commands.entity(radar).add_child(blip);
+        blips.0.push(blip);
+    }
+}
+
+fn refresh_radar_system(
+    settings: Res<Settings>,
+    blips: Res<RadarBlips>,
+    mut radar: Query<&mut Visibility, With<RadarMarker>>,
+    mut blip_nodes: Query<
+        (&mut Style, &mut BackgroundColor, &mut Visibility),
+        Without<RadarMarker>,
+    >,
+    enemies: Query<(&GlobalTransform, &Kulay, &MovementSpeed), With<Enemy>>,
+    cam: Query<&GlobalTransform, With<CamMarker>>,
+) {
+    for mut visibility in radar.iter_mut() {
+        *visibility = if settings.show_radar {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !settings.show_radar {
+        return;
+    }
+    let Ok(cam_transform) = cam.get_single() else {
+        return;
+    };
+    let to_cam = cam_transform.affine().inverse();
+
+    let mut plotted = enemies.iter().filter_map(|(transform, color, ms)| {
+        let local = to_cam.transform_point3(transform.translation());
+        let offset = Vec2::new(local.x, local.z) / RADAR_RANGE * (RADAR_SIZE / 2.);
+        (offset.length() <= RADAR_SIZE / 2.).then_some((offset, *color, ms.0))
+    });
+    for &blip in &blips.0 {
+        let Ok((mut style, mut background, mut visibility)) = blip_nodes.get_mut(blip) else {
+            continue;
+        };
+        let Some((offset, color, speed)) = plotted.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let size = RADAR_BLIP_SIZE * speed / RADAR_BASE_SPEED;
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+        style.left = Val::Px(RADAR_SIZE / 2. + offset.x - size / 2.);
+        style.top = Val::Px(RADAR_SIZE / 2. + offset.y - size / 2.);
+        *background = BackgroundColor(color.color());
+        *visibility = Visibility::Inherited;
+    }
 }
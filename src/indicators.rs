@@ -0,0 +1,224 @@
+use crate::enemy::Enemy;
+use crate::globals::{GameState, Kulay};
+use crate::hud::HudEntities;
+use crate::player::CamMarker;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+const ARROW_SIZE: f32 = 22.;
+const EDGE_MARGIN: f32 = 40.;
+const RING_SIZE: f32 = 48.;
+// enemies closer than this to the player start pulsing a warning ring
+const WARNING_DISTANCE: f32 = 20.;
+
+pub struct ThreatIndicatorPlugin;
+
+impl Plugin for ThreatIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ThreatIndicators(HashMap::new()))
+            .add_systems(OnEnter(GameState::InGame), clear_threat_indicators)
+            .add_systems(
+                Update,
+                (
+                    sync_threat_indicators_system,
+                    update_threat_arrow_system,
+                    update_threat_ring_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// enemy -> (arrow, ring) hud nodes
+#[derive(Resource)]
+struct ThreatIndicators(HashMap<Entity, (Entity, Entity)>);
+
+#[derive(Component)]
+struct ThreatArrow(Entity);
+
+#[derive(Component)]
+struct ThreatRing(Entity);
+
+struct ScreenPlacement {
+    pos: Vec2,
+    on_screen: bool,
+    angle: f32,
+}
+
+// the hud was rebuilt, so every node we were tracking is gone
+fn clear_threat_indicators(mut indicators: ResMut<ThreatIndicators>) {
+    indicators.0.clear();
+}
+
+fn place_on_screen(
+    cam: &Camera,
+    cam_transform: &GlobalTransform,
+    pos: Vec3,
+) -> Option<ScreenPlacement> {
+    let size = cam.logical_viewport_size()?;
+    let center = size / 2.;
+    let ndc = cam.world_to_ndc(cam_transform, pos)?;
+    let behind = (pos - cam_transform.translation()).dot(*cam_transform.forward()) <= 0.;
+
+    // ndc is y up, the ui is y down, and points behind the camera come out mirrored
+    let mut offset = Vec2::new(ndc.x, -ndc.y) * center;
+    if behind {
+        offset = -offset;
+    }
+    let on_screen = !behind && ndc.x.abs() <= 1. && ndc.y.abs() <= 1.;
+    let angle = offset.y.atan2(offset.x);
+    if on_screen {
+        return Some(ScreenPlacement {
+            pos: center + offset,
+            on_screen,
+            angle,
+        });
+    }
+
+    let half = center - Vec2::splat(EDGE_MARGIN);
+    let fit = (half.x / offset.x.abs()).min(half.y / offset.y.abs());
+    if !fit.is_finite() {
+        return None;
+    }
+    Some(ScreenPlacement {
+        pos: center + offset * fit,
+        on_screen,
+        angle,
+    })
+}
+
+fn sync_threat_indicators_system(
+    mut commands: Commands,
+    enemies: Query<Entity, With<Enemy>>,
+    mut indicators: ResMut<ThreatIndicators>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
+    indicators.0.retain(|enemy, (arrow, ring)| {
+        if enemies.contains(*enemy) {
+            return true;
+        }
+        for node in [*arrow, *ring] {
+            if let Some(node) = commands.get_entity(node) {
+                node.despawn_recursive();
+            }
+        }
+        // the hud list would otherwise keep every indicator a long run ever had
+        hud_entities
+            .0
+            .retain(|entity| *entity != *arrow && *entity != *ring);
+        false
+    });
+
+    for enemy in &enemies {
+        if indicators.0.contains_key(&enemy) {
+            continue;
+        }
+        let arrow = NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(ARROW_SIZE),
+                height: Val::Px(ARROW_SIZE),
+                // two borders on a rotated square make a chevron
+                border: UiRect {
+                    top: Val::Px(5.),
+                    right: Val::Px(5.),
+                    ..default()
+                },
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        };
+        let ring = NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(RING_SIZE),
+                height: Val::Px(RING_SIZE),
+                border: UiRect::all(Val::Px(3.)),
+                ..default()
+            },
+            border_radius: BorderRadius::all(Val::Percent(50.)),
+            visibility: Visibility::Hidden,
+            ..default()
+        };
+        let arrow = commands.spawn((arrow, ThreatArrow(enemy))).id();
+        let ring = commands.spawn((ring, ThreatRing(enemy))).id();
+        hud_entities.0.extend([arrow, ring]);
+        indicators.0.insert(enemy, (arrow, ring));
+    }
+}
+
+fn update_threat_arrow_system(
+    mut arrows: Query<(
+        &ThreatArrow,
+        &mut Style,
+        &mut Transform,
+        &mut Visibility,
+        &mut BorderColor,
+    )>,
+    enemies: Query<(&GlobalTransform, &Kulay), With<Enemy>>,
+    cam: Query<(&GlobalTransform, &Camera), With<CamMarker>>,
+) {
+    let Ok((cam_transform, cam)) = cam.get_single() else {
+        return;
+    };
+    for (arrow, mut style, mut transform, mut visibility, mut border) in arrows.iter_mut() {
+        let Ok((enemy_transform, color)) = enemies.get(arrow.0) else {
+            continue;
+        };
+        let placement = place_on_screen(cam, cam_transform, enemy_transform.translation());
+        let Some(placement) = placement.filter(|placement| !placement.on_screen) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        *border = BorderColor(color.color());
+        style.left = Val::Px(placement.pos.x - ARROW_SIZE / 2.);
+        style.top = Val::Px(placement.pos.y - ARROW_SIZE / 2.);
+        transform.rotation = Quat::from_rotation_z(placement.angle + FRAC_PI_4);
+    }
+}
+
+fn update_threat_ring_system(
+    mut rings: Query<(
+        &ThreatRing,
+        &mut Style,
+        &mut Transform,
+        &mut Visibility,
+        &mut BorderColor,
+    )>,
+    enemies: Query<(&GlobalTransform, &Kulay), With<Enemy>>,
+    cam: Query<(&GlobalTransform, &Camera), With<CamMarker>>,
+    time: Res<Time>,
+) {
+    let Ok((cam_transform, cam)) = cam.get_single() else {
+        return;
+    };
+    for (ring, mut style, mut transform, mut visibility, mut border) in rings.iter_mut() {
+        let Ok((enemy_transform, color)) = enemies.get(ring.0) else {
+            continue;
+        };
+        // the player collider rides on the camera, so measure from there
+        let distance = enemy_transform
+            .translation()
+            .distance(cam_transform.translation());
+        let placement = place_on_screen(cam, cam_transform, enemy_transform.translation());
+        let Some(placement) = placement.filter(|_| distance < WARNING_DISTANCE) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let closeness = 1. - distance / WARNING_DISTANCE;
+        let frequency = 2. + 8. * closeness;
+        let pulse = 1. + 0.25 * (time.elapsed_seconds() * frequency * TAU).sin();
+
+        *visibility = Visibility::Inherited;
+        *border = BorderColor(color.color().with_alpha(0.3 + 0.7 * closeness));
+        style.left = Val::Px(placement.pos.x - RING_SIZE / 2.);
+        style.top = Val::Px(placement.pos.y - RING_SIZE / 2.);
+        transform.scale = Vec3::splat(pulse);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::aim::update_aim_system;
+use crate::mode::GameMode;
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::net::UdpSocket;
+
+// the hand tracker streams to this port on localhost, one message per datagram
+pub const TRACKER_PORT: u16 = 7878;
+
+pub struct PointerInputPlugin;
+
+impl Plugin for PointerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TrackerSocket::bind())
+            .insert_resource(Pointer::default())
+            .add_event::<TrackerMessage>()
+            .add_systems(
+                PreUpdate,
+                (read_tracker_system, update_pointer_system)
+                    .chain()
+                    .before(update_aim_system),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputSource {
+    Mouse,
+    Tracker,
+}
+
+// everything an external tracker can tell the game
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum TrackerMessage {
+    // 0..1 across the camera image, y down, before calibration
+    Aim(Vec2),
+    // thumb to middle finger tip, in the same units as `Aim`
+    Pinch(f32),
+    Fist(bool),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OneEuro {
+    // Hz, lower smooths more while holding still
+    pub min_cutoff: f32,
+    // how quickly the cutoff opens up with speed, lower lags more on fast moves
+    pub beta: f32,
+    pub derivative_cutoff: f32,
+}
+
+// tuned separately for every input source
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FilterConfig {
+    pub one_euro: Option<OneEuro>,
+    pub deadzone: f32, // px
+    // seconds to extrapolate ahead along the filtered velocity
+    pub prediction: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputFilters {
+    pub mouse: FilterConfig,
+    pub tracker: FilterConfig,
+}
+
+// the filtered pointer the aim is built from, in window coordinates
+#[derive(Resource, Default)]
+pub struct Pointer {
+    pub pos: Option<Vec2>,
+    pub source: Option<InputSource>,
+    last_cursor: Option<Vec2>,
+    filter: FilterState,
+}
+
+#[derive(Resource)]
+struct TrackerSocket(Option<UdpSocket>);
+
+#[derive(Default)]
+pub(crate) struct FilterState {
+    anchor: Option<Vec2>,
+    smoothed: Option<Vec2>,
+    velocity: Vec2,
+    last_sample: Option<f32>,
+}
+
+impl Default for OneEuro {
+    fn default() -> Self {
+        Self {
+            min_cutoff: 1.,
+            beta: 0.01,
+            derivative_cutoff: 1.,
+        }
+    }
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            one_euro: None,
+            deadzone: 0.,
+            prediction: 0.,
+        }
+    }
+}
+
+impl Default for InputFilters {
+    fn default() -> Self {
+        Self {
+            // the os already hands us a clean cursor
+            mouse: FilterConfig::default(),
+            tracker: FilterConfig {
+                one_euro: Some(OneEuro::default()),
+                deadzone: 3.,
+                prediction: 0.03,
+            },
+        }
+    }
+}
+
+impl InputFilters {
+    pub fn get(&self, source: InputSource) -> &FilterConfig {
+        match source {
+            InputSource::Mouse => &self.mouse,
+            InputSource::Tracker => &self.tracker,
+        }
+    }
+}
+
+impl TrackerMessage {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        match words.next()? {
+            "aim" => {
+                let x = words.next()?.parse().ok()?;
+                let y = words.next()?.parse().ok()?;
+                Some(TrackerMessage::Aim(Vec2::new(x, y)))
+            }
+            "pinch" => Some(TrackerMessage::Pinch(words.next()?.parse().ok()?)),
+            "fist" => Some(TrackerMessage::Fist(words.next()? == "1")),
+            _ => None,
+        }
+    }
+}
+
+impl TrackerSocket {
+    fn bind() -> Self {
+        let socket = UdpSocket::bind(("127.0.0.1", TRACKER_PORT))
+            .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+        match socket {
+            Ok(socket) => Self(Some(socket)),
+            Err(err) => {
+                warn!("tracker input disabled, couldn't bind port {TRACKER_PORT}: {err}");
+                Self(None)
+            }
+        }
+    }
+}
+
+impl FilterState {
+    // `now` in seconds; trackers rarely sample at the frame rate, so time is
+    // measured between samples rather than frames
+    pub(crate) fn apply(&mut self, config: &FilterConfig, raw: Vec2, now: f32) -> Vec2 {
+        let dt = self.last_sample.map_or(0., |last| now - last);
+        self.last_sample = Some(now);
+
+        // the deadzone drags an anchor around instead of snapping, so it never jumps
+        let anchor = self.anchor.unwrap_or(raw);
+        let offset = raw - anchor;
+        let anchor = if offset.length() > config.deadzone {
+            raw - offset.normalize_or_zero() * config.deadzone
+        } else {
+            anchor
+        };
+        self.anchor = Some(anchor);
+
+        let previous = self.smoothed.unwrap_or(anchor);
+        let smoothed = match (&config.one_euro, dt > 0.) {
+            (Some(one_euro), true) => {
+                let velocity = (anchor - previous) / dt;
+                self.velocity = self
+                    .velocity
+                    .lerp(velocity, smoothing_factor(one_euro.derivative_cutoff, dt));
+                let cutoff = one_euro.min_cutoff + one_euro.beta * self.velocity.length();
+                previous.lerp(anchor, smoothing_factor(cutoff, dt))
+            }
+            (None, true) => {
+                self.velocity = (anchor - previous) / dt;
+                anchor
+            }
+            _ => previous,
+        };
+        self.smoothed = Some(smoothed);
+
+        smoothed + self.velocity * config.prediction
+    }
+}
+
+// raw tracker coordinates to window coordinates, through the calibration if there is one
+pub fn tracker_to_window(settings: &Settings, win: &Window, raw: Vec2) -> Vec2 {
+    let calibrated = settings
+        .tracker_calibration()
+        .map_or(raw, |calibration| calibration.apply(raw));
+    calibrated * win.size()
+}
+
+fn smoothing_factor(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1. / (TAU * cutoff);
+    1. / (1. + tau / dt)
+}
+
+fn read_tracker_system(socket: Res<TrackerSocket>, mut messages: EventWriter<TrackerMessage>) {
+    let Some(socket) = &socket.0 else {
+        return;
+    };
+    let mut buf = [0; 512];
+    // drain everything that arrived since last frame
+    while let Ok(len) = socket.recv(&mut buf) {
+        let Ok(packet) = std::str::from_utf8(&buf[..len]) else {
+            continue;
+        };
+        messages.send_batch(packet.lines().filter_map(TrackerMessage::parse));
+    }
+}
+
+// whichever source moved last owns the pointer; in co-op the tracker belongs
+// to player two and this is the mouse alone
+pub fn update_pointer_system(
+    mut pointer: ResMut<Pointer>,
+    mut messages: EventReader<TrackerMessage>,
+    win: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    mode: Res<GameMode>,
+    time: Res<Time<Real>>,
+) {
+    let Ok(win) = win.get_single() else {
+        return;
+    };
+    let cursor = win.cursor_position();
+    let tracked = messages
+        .read()
+        .filter_map(|message| match message {
+            TrackerMessage::Aim(raw) if mode.players() == 1 => Some(*raw),
+            _ => None,
+        })
+        .map(|raw| tracker_to_window(&settings, win, raw))
+        .last();
+
+    let sample = match tracked {
+        Some(pos) => Some((InputSource::Tracker, pos)),
+        None if cursor.is_some() && cursor != pointer.last_cursor => {
+            cursor.map(|pos| (InputSource::Mouse, pos))
+        }
+        _ => None,
+    };
+    pointer.last_cursor = cursor;
+
+    let Some((source, raw)) = sample else {
+        return;
+    };
+    if pointer.source != Some(source) {
+        // switching sources starts the filter over rather than smearing between them
+        pointer.filter = FilterState::default();
+        pointer.source = Some(source);
+    }
+    let config = settings.input_filters.get(source);
+    let pos = pointer.filter.apply(config, raw, time.elapsed_seconds());
+    pointer.pos = Some(pos);
+}
@@ -1,5 +1,22 @@
+pub mod aim;
+pub mod audio;
+pub mod calibration;
+pub mod coop;
+pub mod daily;
+pub mod effects;
 pub mod enemy;
+pub mod feedback;
+pub mod gesture;
 pub mod globals;
 pub mod hud;
+pub mod indicators;
+pub mod input;
+pub mod menu;
+pub mod mode;
 pub mod player;
+pub mod powerup;
+pub mod practice;
+pub mod save;
+pub mod settings;
 pub mod startup;
+pub mod synthetic;
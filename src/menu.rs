@@ -0,0 +1,684 @@
+use crate::audio::{PlaySfx, SfxId};
+use crate::calibration::CalibrationState;
+use crate::daily::DailyChallenge;
+use crate::globals::{GameState, PauseState};
+use crate::mode::GameMode;
+use crate::save::Progress;
+use crate::save::SaveEvent;
+use crate::settings::Settings;
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+
+const BUTTON_COLOR: Color = Color::srgba(1., 1., 1., 0.1);
+const BUTTON_HOVER_COLOR: Color = Color::srgba(1., 1., 1., 0.25);
+const SLIDER_TRACK_COLOR: Color = Color::srgba(1., 1., 1., 0.15);
+const SLIDER_FILL_COLOR: Color = Color::srgb(0.42, 0.71, 0.85);
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<SettingsMenuState>()
+            .init_state::<ModeMenuState>()
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (release_cursor_system, init_main_menu),
+            )
+            .add_systems(
+                OnExit(GameState::MainMenu),
+                despawn_screen::<MainMenuMarker>,
+            )
+            .add_systems(OnEnter(ModeMenuState::Open), init_mode_menu)
+            .add_systems(
+                OnExit(ModeMenuState::Open),
+                despawn_screen::<ModeMenuMarker>,
+            )
+            .add_systems(
+                OnEnter(PauseState::Paused),
+                (release_cursor_system, pause_time_system, init_pause_menu),
+            )
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (unpause_time_system, despawn_screen::<PauseMenuMarker>),
+            )
+            .add_systems(OnEnter(PauseState::Running), grab_cursor_system)
+            .add_systems(OnEnter(SettingsMenuState::Open), init_settings_menu)
+            .add_systems(
+                OnExit(SettingsMenuState::Open),
+                (
+                    despawn_screen::<SettingsMenuMarker>,
+                    persist_settings_system,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    menu_button_system,
+                    button_hover_system,
+                    toggle_menu_system.run_if(input_just_pressed(KeyCode::Escape)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    slider_drag_system,
+                    refresh_settings_menu_system.run_if(resource_changed::<Settings>),
+                )
+                    .chain()
+                    .run_if(in_state(SettingsMenuState::Open)),
+            );
+    }
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SettingsMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ModeMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Component)]
+struct MainMenuMarker;
+
+#[derive(Component)]
+struct ModeMenuMarker;
+
+#[derive(Component)]
+struct PauseMenuMarker;
+
+#[derive(Component)]
+struct SettingsMenuMarker;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Play,
+    Start(GameMode),
+    CloseModes,
+    Resume,
+    Quit,
+    OpenSettings,
+    CloseSettings,
+    Calibrate,
+    Toggle(SettingToggle),
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingSlider {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    UiVolume,
+    Sensitivity,
+    Fov,
+    FogStart,
+    FogEnd,
+    DamageFeedback,
+    AimAssist,
+    AimAssistRadius,
+}
+
+#[derive(Component)]
+struct SliderFill(SettingSlider);
+
+#[derive(Component)]
+struct SliderValue(SettingSlider);
+
+#[derive(Component, Clone, Copy)]
+enum SettingToggle {
+    Fullscreen,
+    ReducedMotion,
+    Radar,
+}
+
+#[derive(Component)]
+struct ToggleLabel(SettingToggle);
+
+impl SettingSlider {
+    const ALL: [SettingSlider; 11] = [
+        SettingSlider::MasterVolume,
+        SettingSlider::MusicVolume,
+        SettingSlider::SfxVolume,
+        SettingSlider::UiVolume,
+        SettingSlider::Sensitivity,
+        SettingSlider::Fov,
+        SettingSlider::FogStart,
+        SettingSlider::FogEnd,
+        SettingSlider::DamageFeedback,
+        SettingSlider::AimAssist,
+        SettingSlider::AimAssistRadius,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SettingSlider::MasterVolume => "Master Volume",
+            SettingSlider::MusicVolume => "Music Volume",
+            SettingSlider::SfxVolume => "SFX Volume",
+            SettingSlider::UiVolume => "UI Volume",
+            SettingSlider::Sensitivity => "Aim Sensitivity",
+            SettingSlider::Fov => "Field of View",
+            SettingSlider::FogStart => "Fog Start",
+            SettingSlider::FogEnd => "Fog End",
+            SettingSlider::DamageFeedback => "Damage Feedback",
+            SettingSlider::AimAssist => "Aim Assist",
+            SettingSlider::AimAssistRadius => "Assist Radius",
+        }
+    }
+
+    fn range(self) -> (f32, f32) {
+        match self {
+            SettingSlider::MasterVolume
+            | SettingSlider::MusicVolume
+            | SettingSlider::SfxVolume
+            | SettingSlider::UiVolume => (0., 1.),
+            SettingSlider::Sensitivity => (0.0001, 0.002),
+            SettingSlider::Fov => (30., 100.),
+            SettingSlider::FogStart => (0., 100.),
+            SettingSlider::FogEnd => (20., 300.),
+            SettingSlider::DamageFeedback | SettingSlider::AimAssist => (0., 1.),
+            SettingSlider::AimAssistRadius => (20., 200.),
+        }
+    }
+
+    fn get(self, settings: &Settings) -> f32 {
+        match self {
+            SettingSlider::MasterVolume => settings.master_volume,
+            SettingSlider::MusicVolume => settings.music_volume,
+            SettingSlider::SfxVolume => settings.sfx_volume,
+            SettingSlider::UiVolume => settings.ui_volume,
+            SettingSlider::Sensitivity => settings.mouse_sensitivity,
+            SettingSlider::Fov => settings.fov,
+            SettingSlider::FogStart => settings.fog_start,
+            SettingSlider::FogEnd => settings.fog_end,
+            SettingSlider::DamageFeedback => settings.damage_feedback,
+            SettingSlider::AimAssist => settings.aim_assist,
+            SettingSlider::AimAssistRadius => settings.aim_assist_radius,
+        }
+    }
+
+    fn set(self, settings: &mut Settings, value: f32) {
+        match self {
+            SettingSlider::MasterVolume => settings.master_volume = value,
+            SettingSlider::MusicVolume => settings.music_volume = value,
+            SettingSlider::SfxVolume => settings.sfx_volume = value,
+            SettingSlider::UiVolume => settings.ui_volume = value,
+            SettingSlider::Sensitivity => settings.mouse_sensitivity = value,
+            SettingSlider::Fov => settings.fov = value,
+            // keep the fog band from turning inside out
+            SettingSlider::FogStart => settings.fog_start = value.min(settings.fog_end - 1.),
+            SettingSlider::FogEnd => settings.fog_end = value.max(settings.fog_start + 1.),
+            SettingSlider::DamageFeedback => settings.damage_feedback = value,
+            SettingSlider::AimAssist => settings.aim_assist = value,
+            SettingSlider::AimAssistRadius => settings.aim_assist_radius = value,
+        }
+    }
+
+    fn display(self, settings: &Settings) -> String {
+        let value = self.get(settings);
+        match self {
+            SettingSlider::MasterVolume
+            | SettingSlider::MusicVolume
+            | SettingSlider::SfxVolume
+            | SettingSlider::UiVolume
+            | SettingSlider::DamageFeedback
+            | SettingSlider::AimAssist => {
+                format!("{:.0}%", value * 100.)
+            }
+            SettingSlider::Sensitivity => format!("{:.2}", value * 1000.),
+            SettingSlider::Fov => format!("{:.0}°", value),
+            SettingSlider::FogStart | SettingSlider::FogEnd => format!("{:.0}", value),
+            SettingSlider::AimAssistRadius => format!("{:.0}px", value),
+        }
+    }
+
+    fn normalized(self, settings: &Settings) -> f32 {
+        let (min, max) = self.range();
+        ((self.get(settings) - min) / (max - min)).clamp(0., 1.)
+    }
+}
+
+impl SettingToggle {
+    const ALL: [SettingToggle; 3] = [
+        SettingToggle::Fullscreen,
+        SettingToggle::ReducedMotion,
+        SettingToggle::Radar,
+    ];
+
+    fn get(self, settings: &Settings) -> bool {
+        match self {
+            SettingToggle::Fullscreen => settings.fullscreen,
+            SettingToggle::ReducedMotion => settings.reduced_motion,
+            SettingToggle::Radar => settings.show_radar,
+        }
+    }
+
+    fn flip(self, settings: &mut Settings) {
+        match self {
+            SettingToggle::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingToggle::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            SettingToggle::Radar => settings.show_radar = !settings.show_radar,
+        }
+    }
+
+    fn display(self, settings: &Settings) -> String {
+        let label = match self {
+            SettingToggle::Fullscreen => "Fullscreen",
+            SettingToggle::ReducedMotion => "Reduced Motion",
+            SettingToggle::Radar => "Radar",
+        };
+        format!(
+            "{}: {}",
+            label,
+            if self.get(settings) { "On" } else { "Off" }
+        )
+    }
+}
+
+pub fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn release_cursor_system(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in windows.iter_mut() {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+fn grab_cursor_system(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in windows.iter_mut() {
+        window.cursor.grab_mode = CursorGrabMode::Locked;
+        window.cursor.visible = false;
+    }
+}
+
+fn pause_time_system(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time_system(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn persist_settings_system(mut save_event: EventWriter<SaveEvent>) {
+    save_event.send(SaveEvent);
+}
+
+fn toggle_menu_system(
+    game_state: Res<State<GameState>>,
+    pause_state: Option<Res<State<PauseState>>>,
+    settings_menu: Res<State<SettingsMenuState>>,
+    mode_menu: Res<State<ModeMenuState>>,
+    calibration: Res<State<CalibrationState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_menu: ResMut<NextState<SettingsMenuState>>,
+    mut next_mode_menu: ResMut<NextState<ModeMenuState>>,
+    mut next_calibration: ResMut<NextState<CalibrationState>>,
+) {
+    if *calibration.get() == CalibrationState::Running {
+        next_calibration.set(CalibrationState::Off);
+        return;
+    }
+    if *settings_menu.get() == SettingsMenuState::Open {
+        next_settings_menu.set(SettingsMenuState::Closed);
+        return;
+    }
+    if *mode_menu.get() == ModeMenuState::Open {
+        next_mode_menu.set(ModeMenuState::Closed);
+        return;
+    }
+    if *game_state.get() != GameState::InGame {
+        return;
+    }
+    let Some(pause_state) = pause_state else {
+        return;
+    };
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+fn menu_button_system(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_menu: ResMut<NextState<SettingsMenuState>>,
+    mut next_mode_menu: ResMut<NextState<ModeMenuState>>,
+    mut next_calibration: ResMut<NextState<CalibrationState>>,
+    mut settings: ResMut<Settings>,
+    mut mode: ResMut<GameMode>,
+    mut play_sfx: EventWriter<PlaySfx>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        play_sfx.send(PlaySfx(SfxId::Click));
+        match button {
+            MenuButton::Play => next_mode_menu.set(ModeMenuState::Open),
+            MenuButton::Start(picked) => {
+                *mode = *picked;
+                next_mode_menu.set(ModeMenuState::Closed);
+                next_game_state.set(GameState::InGame);
+            }
+            MenuButton::CloseModes => next_mode_menu.set(ModeMenuState::Closed),
+            MenuButton::Resume => next_pause_state.set(PauseState::Running),
+            MenuButton::Quit => next_game_state.set(GameState::MainMenu),
+            MenuButton::OpenSettings => next_settings_menu.set(SettingsMenuState::Open),
+            MenuButton::CloseSettings => next_settings_menu.set(SettingsMenuState::Closed),
+            MenuButton::Calibrate => next_calibration.set(CalibrationState::Running),
+            MenuButton::Toggle(toggle) => toggle.flip(&mut settings),
+        }
+    }
+}
+
+fn button_hover_system(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<MenuButton>),
+    >,
+) {
+    for (interaction, mut color) in buttons.iter_mut() {
+        *color = match interaction {
+            Interaction::None => BackgroundColor(BUTTON_COLOR),
+            Interaction::Hovered | Interaction::Pressed => BackgroundColor(BUTTON_HOVER_COLOR),
+        };
+    }
+}
+
+fn slider_drag_system(
+    sliders: Query<(&Interaction, &RelativeCursorPosition, &SettingSlider)>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, cursor, slider) in &sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(pos) = cursor.normalized else {
+            continue;
+        };
+        let (min, max) = slider.range();
+        let value = min + pos.x.clamp(0., 1.) * (max - min);
+        if slider.get(&settings) != value {
+            slider.set(&mut settings, value);
+        }
+    }
+}
+
+fn refresh_settings_menu_system(
+    settings: Res<Settings>,
+    mut fills: Query<(&mut Style, &SliderFill)>,
+    mut values: Query<(&mut Text, &SliderValue), Without<ToggleLabel>>,
+    mut toggle_labels: Query<(&mut Text, &ToggleLabel)>,
+) {
+    for (mut style, fill) in fills.iter_mut() {
+        style.width = Val::Percent(fill.0.normalized(&settings) * 100.);
+    }
+    for (mut text, value) in values.iter_mut() {
+        text.sections[0].value = value.0.display(&settings);
+    }
+    for (mut text, label) in toggle_labels.iter_mut() {
+        text.sections[0].value = label.0.display(&settings);
+    }
+}
+
+pub fn screen_bundle(background: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: BackgroundColor(background),
+        ..default()
+    }
+}
+
+fn spawn_button(
+    commands: &mut Commands,
+    parent: Entity,
+    label: &str,
+    button: MenuButton,
+) -> Entity {
+    let button_bundle = ButtonBundle {
+        style: Style {
+            width: Val::Px(320.),
+            padding: UiRect::all(Val::Px(10.)),
+            margin: UiRect::top(Val::Px(12.)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        border_radius: BorderRadius::all(Val::Px(6.)),
+        background_color: BackgroundColor(BUTTON_COLOR),
+        ..default()
+    };
+    let text = TextBundle::from_section(
+        label,
+        TextStyle {
+            font_size: 32.,
+            ..default()
+        },
+    );
+    let text = commands.spawn(text).id();
+    let button = commands
+        .spawn((button_bundle, button))
+        .push_children(&[text])
+        .id();
+    commands.entity(parent).push_children(&[button]);
+    text
+}
+
+pub fn spawn_title(commands: &mut Commands, parent: Entity, title: &str) {
+    let text = TextBundle {
+        style: Style {
+            margin: UiRect::bottom(Val::Percent(2.)),
+            ..default()
+        },
+        text: Text::from_section(
+            title,
+            TextStyle {
+                font_size: 64.,
+                ..default()
+            },
+        ),
+        ..default()
+    };
+    let text = commands.spawn(text).id();
+    commands.entity(parent).push_children(&[text]);
+}
+
+fn init_main_menu(mut commands: Commands) {
+    let screen = commands
+        .spawn((MainMenuMarker, screen_bundle(Color::NONE)))
+        .id();
+    spawn_title(&mut commands, screen, "Handgun Heroes");
+    spawn_button(&mut commands, screen, "Play", MenuButton::Play);
+    spawn_button(&mut commands, screen, "Settings", MenuButton::OpenSettings);
+    spawn_button(
+        &mut commands,
+        screen,
+        "Calibrate Tracker",
+        MenuButton::Calibrate,
+    );
+}
+
+fn init_mode_menu(mut commands: Commands, progress: Res<Progress>) {
+    let screen = commands
+        .spawn((ModeMenuMarker, screen_bundle(Color::srgba(0., 0., 0., 0.9))))
+        // stay above the main menu, and keep its buttons from catching clicks
+        .insert(ZIndex::Global(10))
+        .insert(FocusPolicy::Block)
+        .id();
+    spawn_title(&mut commands, screen, "Select Mode");
+    for mode in GameMode::ALL {
+        spawn_button(&mut commands, screen, mode.label(), MenuButton::Start(mode));
+    }
+
+    let daily = DailyChallenge::today();
+    let attempt = match progress.daily.get(&daily.day) {
+        Some(score) => format!("played today, scored {}; replays are practice", score),
+        None => "one official attempt today".to_string(),
+    };
+    let daily_text = TextBundle::from_section(
+        format!("Daily {}: {}\n{}", daily.date(), daily.describe(), attempt),
+        TextStyle {
+            font_size: 24.,
+            ..default()
+        },
+    )
+    .with_text_justify(JustifyText::Center)
+    .with_style(Style {
+        margin: UiRect::top(Val::Px(12.)),
+        ..default()
+    });
+    let daily_text = commands.spawn(daily_text).id();
+    commands.entity(screen).push_children(&[daily_text]);
+    spawn_button(&mut commands, screen, "Back", MenuButton::CloseModes);
+}
+
+fn init_pause_menu(mut commands: Commands) {
+    let screen = commands
+        .spawn((
+            PauseMenuMarker,
+            screen_bundle(Color::srgba(0., 0., 0., 0.6)),
+        ))
+        .id();
+    spawn_title(&mut commands, screen, "Paused");
+    spawn_button(&mut commands, screen, "Resume", MenuButton::Resume);
+    spawn_button(&mut commands, screen, "Settings", MenuButton::OpenSettings);
+    spawn_button(&mut commands, screen, "Quit to Menu", MenuButton::Quit);
+}
+
+fn init_settings_menu(mut commands: Commands, settings: Res<Settings>) {
+    let screen = commands
+        .spawn((
+            SettingsMenuMarker,
+            screen_bundle(Color::srgba(0., 0., 0., 0.9)),
+        ))
+        // stay above the main menu and pause overlay, and keep their buttons
+        // from catching clicks and slider drags
+        .insert(ZIndex::Global(10))
+        .insert(FocusPolicy::Block)
+        .id();
+    spawn_title(&mut commands, screen, "Settings");
+
+    // sliders flow into a second column once the first one fills up
+    let grid = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            flex_wrap: FlexWrap::Wrap,
+            align_content: AlignContent::Center,
+            column_gap: Val::Px(40.),
+            height: Val::Percent(50.),
+            ..default()
+        },
+        ..default()
+    };
+    let grid = commands.spawn(grid).id();
+    commands.entity(screen).push_children(&[grid]);
+
+    let label_style = TextStyle {
+        font_size: 24.,
+        ..default()
+    };
+    for slider in SettingSlider::ALL {
+        let row = NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                align_items: AlignItems::Center,
+                margin: UiRect::vertical(Val::Px(6.)),
+                ..default()
+            },
+            ..default()
+        };
+        let label =
+            TextBundle::from_section(slider.label(), label_style.clone()).with_style(Style {
+                width: Val::Px(220.),
+                ..default()
+            });
+        let track = NodeBundle {
+            style: Style {
+                width: Val::Px(300.),
+                height: Val::Px(16.),
+                ..default()
+            },
+            border_radius: BorderRadius::all(Val::Px(8.)),
+            background_color: BackgroundColor(SLIDER_TRACK_COLOR),
+            ..default()
+        };
+        let fill = NodeBundle {
+            style: Style {
+                width: Val::Percent(slider.normalized(&settings) * 100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            border_radius: BorderRadius::all(Val::Px(8.)),
+            background_color: BackgroundColor(SLIDER_FILL_COLOR),
+            ..default()
+        };
+        let value = TextBundle::from_section(slider.display(&settings), label_style.clone())
+            .with_style(Style {
+                width: Val::Px(100.),
+                margin: UiRect::left(Val::Px(16.)),
+                ..default()
+            });
+
+        let label = commands.spawn(label).id();
+        let fill = commands.spawn((fill, SliderFill(slider))).id();
+        let track = commands
+            .spawn((
+                track,
+                slider,
+                Interaction::default(),
+                RelativeCursorPosition::default(),
+            ))
+            .push_children(&[fill])
+            .id();
+        let value = commands.spawn((value, SliderValue(slider))).id();
+        let row = commands
+            .spawn(row)
+            .push_children(&[label, track, value])
+            .id();
+        commands.entity(grid).push_children(&[row]);
+    }
+
+    let toggles = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(20.),
+            ..default()
+        },
+        ..default()
+    };
+    let toggles = commands.spawn(toggles).id();
+    commands.entity(screen).push_children(&[toggles]);
+    for toggle in SettingToggle::ALL {
+        let text = spawn_button(
+            &mut commands,
+            toggles,
+            &toggle.display(&settings),
+            MenuButton::Toggle(toggle),
+        );
+        commands.entity(text).insert(ToggleLabel(toggle));
+    }
+    spawn_button(&mut commands, screen, "Back", MenuButton::CloseSettings);
+}
@@ -0,0 +1,362 @@
+use crate::daily::{DailyChallenge, DailyRun};
+use crate::enemy::{DifficultyCurve, EnemyState, SpawnRng, WaveConfig};
+use crate::globals::{GameState, PauseState};
+use crate::hud::{clean_hud_system, HudEntities};
+use crate::player::{MaxHealth, PlayerHealth, STARTING_HEALTH};
+use crate::powerup::PowerUpKind;
+use crate::save::{Progress, SaveEvent};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// points lost per hit in modes where hits don't cost hearts
+pub const DAMAGE_PENALTY: i32 = 200;
+const HARDCORE_SPEED: f32 = 1.3;
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameMode::default())
+            .insert_resource(RoundTimer(None))
+            .insert_resource(DailyRun::default())
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (
+                    start_round_system,
+                    (init_mode_hud, init_round_timer_hud, init_wave_hud),
+                )
+                    .chain()
+                    .after(clean_hud_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    (round_timer_system, refresh_round_timer_hud_system).chain(),
+                    refresh_wave_hud_system,
+                )
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+// picked from the main menu, and kept for replays from the game over screen
+#[derive(
+    Resource,
+    Serialize,
+    Deserialize,
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    TimeAttack60,
+    TimeAttack120,
+    Endless,
+    Daily,
+    Practice,
+    Hardcore,
+    Coop,
+}
+
+// counts down on game time, so slow motion buys a little extra
+#[derive(Resource)]
+pub struct RoundTimer(pub Option<Timer>);
+
+#[derive(Component)]
+struct RoundTimerMarker;
+
+#[derive(Component)]
+struct WaveMarker;
+
+#[derive(Component)]
+struct ModeMarker;
+
+impl RoundTimer {
+    pub fn for_mode(mode: GameMode) -> Self {
+        Self(
+            mode.time_limit()
+                .map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+        )
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 8] = [
+        GameMode::Classic,
+        GameMode::TimeAttack60,
+        GameMode::TimeAttack120,
+        GameMode::Endless,
+        GameMode::Daily,
+        GameMode::Practice,
+        GameMode::Hardcore,
+        GameMode::Coop,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::TimeAttack60 => "Time Attack 60s",
+            GameMode::TimeAttack120 => "Time Attack 120s",
+            GameMode::Endless => "Endless",
+            GameMode::Daily => "Daily Challenge",
+            GameMode::Practice => "Practice Range",
+            GameMode::Hardcore => "Hardcore",
+            GameMode::Coop => "Co-op",
+        }
+    }
+
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::Classic
+            | GameMode::Endless
+            | GameMode::Daily
+            | GameMode::Practice
+            | GameMode::Hardcore
+            | GameMode::Coop => None,
+            GameMode::TimeAttack60 => Some(60.),
+            GameMode::TimeAttack120 => Some(120.),
+        }
+    }
+
+    // hits cost DAMAGE_PENALTY points instead of a heart
+    pub fn unlimited_health(self) -> bool {
+        self.time_limit().is_some()
+    }
+
+    pub fn starting_health(self) -> usize {
+        match self {
+            GameMode::Hardcore => 1,
+            _ => STARTING_HEALTH,
+        }
+    }
+
+    // wrong-color hits cost a life and misses cut the combo
+    pub fn strict(self) -> bool {
+        self == GameMode::Hardcore
+    }
+
+    // player two joins on a gamepad or the hand tracker, sharing the hearts
+    pub fn players(self) -> usize {
+        match self {
+            GameMode::Coop => 2,
+            _ => 1,
+        }
+    }
+
+    // enemies that reach the player just vanish
+    pub fn damage_free(self) -> bool {
+        self == GameMode::Practice
+    }
+
+    pub fn ranked(self) -> bool {
+        self != GameMode::Practice
+    }
+
+    pub fn wave_config(self) -> WaveConfig {
+        match self {
+            // the daily's modifiers are layered on when the run starts
+            // practice turns its own knobs once the run starts
+            GameMode::Classic | GameMode::Daily | GameMode::Practice => WaveConfig::default(),
+            GameMode::TimeAttack60 | GameMode::TimeAttack120 => WaveConfig {
+                extra_enemies: 3,
+                // hearts are no use when nothing takes them away
+                powerups_per_minute: WaveConfig::default()
+                    .powerups_per_minute
+                    .into_iter()
+                    .filter(|(kind, _)| *kind != PowerUpKind::Heart)
+                    .collect(),
+                ..default()
+            },
+            GameMode::Hardcore => WaveConfig {
+                speed_scale: HARDCORE_SPEED,
+                // one hit means one hit, no hearts or shields to soften it
+                powerups_per_minute: WaveConfig::default()
+                    .powerups_per_minute
+                    .into_iter()
+                    .filter(|(kind, _)| !matches!(kind, PowerUpKind::Heart | PowerUpKind::Shield))
+                    .collect(),
+                ..default()
+            },
+            // two guns clear the field twice as fast
+            GameMode::Coop => WaveConfig {
+                extra_enemies: 2,
+                ..default()
+            },
+            GameMode::Endless => WaveConfig {
+                curve: Some(DifficultyCurve::default()),
+                ..default()
+            },
+        }
+    }
+}
+
+pub fn start_round_system(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut progress: ResMut<Progress>,
+    mut save_event: EventWriter<SaveEvent>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut daily_run: ResMut<DailyRun>,
+    mut spawn_rng: ResMut<SpawnRng>,
+    mut health: ResMut<PlayerHealth>,
+    mut max_health: ResMut<MaxHealth>,
+) {
+    health.0 = mode.starting_health();
+    max_health.0 = mode.starting_health();
+    let mut wave_config = mode.wave_config();
+    let challenge = (*mode == GameMode::Daily).then(DailyChallenge::today);
+    match &challenge {
+        Some(challenge) => {
+            for modifier in &challenge.modifiers {
+                modifier.apply(&mut wave_config);
+            }
+            *spawn_rng = SpawnRng::seeded(challenge.spawn_seed());
+            daily_run.official = !progress.daily.contains_key(&challenge.day);
+            if daily_run.official {
+                // the attempt is spent as soon as it starts, so quitting or
+                // resetting a bad run doesn't buy another go
+                progress.daily.insert(challenge.day, 0);
+                save_event.send(SaveEvent);
+            }
+        }
+        None => *spawn_rng = SpawnRng::from_entropy(),
+    }
+    daily_run.challenge = challenge;
+    commands.insert_resource(wave_config);
+    *round_timer = RoundTimer::for_mode(*mode);
+}
+
+fn init_mode_hud(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
+    // under the scoreboard
+    let row = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            top: Val::Px(110.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    };
+    let row = commands.spawn(row).id();
+    hud_entities.0.push(row);
+
+    let text = TextBundle::from_section(
+        mode.label(),
+        TextStyle {
+            font_size: 24.,
+            color: Color::srgba(1., 1., 1., 0.7),
+            ..default()
+        },
+    );
+    let text = commands.spawn((text, ModeMarker)).id();
+    commands.entity(row).add_child(text);
+}
+
+fn round_timer_system(
+    mut round_timer: ResMut<RoundTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    let Some(timer) = &mut round_timer.0 else {
+        return;
+    };
+    if timer.tick(time.delta()).just_finished() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn init_round_timer_hud(
+    mut commands: Commands,
+    round_timer: Res<RoundTimer>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
+    if round_timer.0.is_none() {
+        return;
+    }
+    let text = TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(50.),
+            top: Val::Px(50.),
+            ..default()
+        },
+        ..default()
+    };
+    let text = commands.spawn((text, RoundTimerMarker)).id();
+    hud_entities.0.push(text);
+}
+
+fn refresh_round_timer_hud_system(
+    round_timer: Res<RoundTimer>,
+    mut hud: Query<&mut Text, With<RoundTimerMarker>>,
+) {
+    let Some(timer) = &round_timer.0 else {
+        return;
+    };
+    let remaining = timer.remaining_secs().ceil() as u32;
+    // the last ten seconds go red
+    let color = if remaining <= 10 {
+        Color::srgb(1., 0.3, 0.3)
+    } else {
+        Color::WHITE
+    };
+    for mut text in hud.iter_mut() {
+        *text = Text::from_section(
+            format!("{}:{:02}", remaining / 60, remaining % 60),
+            TextStyle {
+                font_size: 50.,
+                color,
+                ..default()
+            },
+        );
+    }
+}
+
+fn init_wave_hud(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut hud_entities: ResMut<HudEntities>,
+) {
+    if mode.wave_config().curve.is_none() {
+        return;
+    }
+    let text = TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(50.),
+            top: Val::Px(50.),
+            ..default()
+        },
+        ..default()
+    };
+    let text = commands.spawn((text, WaveMarker)).id();
+    hud_entities.0.push(text);
+}
+
+fn refresh_wave_hud_system(
+    enemy_state: Res<EnemyState>,
+    mut hud: Query<&mut Text, With<WaveMarker>>,
+) {
+    for mut text in hud.iter_mut() {
+        *text = Text::from_section(
+            format!("Wave {}", enemy_state.wave),
+            TextStyle {
+                font_size: 50.,
+                ..default()
+            },
+        );
+    }
+}
@@ -1,24 +1,54 @@
-use crate::enemy::{eliminate_enemy, EnemyState};
-use crate::globals::Kulay;
+use crate::aim::AimAssist;
+use crate::audio::{PlaySfxAt, SfxId};
+use crate::coop::SecondPlayer;
+use crate::daily::{DailyRun, Modifier, SWITCH_LOCK_SECS};
+use crate::effects::{EffectEvent, HitMarker};
+use crate::enemy::{eliminate_enemy, EnemyState, SpawnedAt, WaveConfig};
+use crate::gesture::{GestureAction, Gestures};
+use crate::globals::{DamageEvent, Kulay, PauseState};
 use crate::hud::*;
-use bevy::input::common_conditions::input_just_pressed;
+use crate::mode::GameMode;
+use crate::powerup::{ActivePowerUps, PowerUp, PowerUpCollected, PowerUpKind};
+use crate::settings::Settings;
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
 use bevy_rapier3d::prelude::*;
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+
+// how far the camera may yaw/pitch away from straight ahead, in radians
+pub const LOOK_CLAMP: f32 = 0.1;
+pub const STARTING_HEALTH: usize = 5;
+const SHOT_RANGE: f32 = 255.;
+// pellets around the center ray, and the cone they fan out in (radians)
+const SPREAD_PELLETS: usize = 6;
+const SPREAD_ANGLE: f32 = 0.06;
+const SPLASH_RADIUS: f32 = 3.;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayerHealth(5))
-            .insert_resource(PlayerWeapon(Kulay::Asul))
+        app.insert_resource(PlayerHealth(STARTING_HEALTH))
+            .insert_resource(MaxHealth(STARTING_HEALTH))
+            .insert_resource(PlayerShield(0))
             .insert_resource(KillCount(0))
+            .add_event::<FireEvent>()
+            .add_event::<ShotResult>()
             .add_systems(Startup, init_player)
-            .add_systems(Update, (player_movement_system, switch_weapon_system))
             .add_systems(
                 Update,
-                (player_shoot_system).run_if(input_just_pressed(MouseButton::Left)),
+                (
+                    player_movement_system,
+                    (
+                        switch_weapon_system,
+                        weapon_trigger_system,
+                        player_shoot_system,
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(PauseState::Running)),
             );
     }
 }
@@ -29,8 +59,115 @@ pub struct CamMarker;
 #[derive(Resource)]
 pub struct PlayerHealth(pub usize);
 
+// number of heart containers, health never goes above it
+#[derive(Resource)]
+pub struct MaxHealth(pub usize);
+
+// each point soaks up one hit before health is touched
 #[derive(Resource)]
-pub struct PlayerWeapon(pub Kulay);
+pub struct PlayerShield(pub usize);
+
+// everything that sets one gun apart from another
+pub struct WeaponSpec {
+    pub name: &'static str,
+    pub mode: WeaponMode,
+    // seconds between shots
+    pub cooldown: f32,
+    pub magazine: u32,
+    pub reload: f32,
+    // seconds the trigger has to be held, the shot goes off on release
+    pub charge: Option<f32>,
+}
+
+pub const WEAPONS: [WeaponSpec; 4] = [
+    WeaponSpec {
+        name: "Pistol",
+        mode: WeaponMode::Single,
+        cooldown: 0.2,
+        magazine: 12,
+        reload: 1.,
+        charge: None,
+    },
+    WeaponSpec {
+        name: "Shotgun",
+        mode: WeaponMode::Spread,
+        cooldown: 0.8,
+        magazine: 6,
+        reload: 1.8,
+        charge: None,
+    },
+    WeaponSpec {
+        name: "Rail",
+        mode: WeaponMode::Pierce,
+        cooldown: 1.,
+        magazine: 4,
+        reload: 2.,
+        charge: Some(0.6),
+    },
+    WeaponSpec {
+        name: "Launcher",
+        mode: WeaponMode::Splash,
+        cooldown: 1.2,
+        magazine: 3,
+        reload: 2.5,
+        charge: None,
+    },
+];
+
+// player one's lives on the camera, player two's on an entity of its own;
+// `weapon` indexes into `WEAPONS`
+#[derive(Component)]
+pub struct PlayerWeapon {
+    pub weapon: usize,
+    pub color: Kulay,
+    pub ammo: u32,
+    // seconds left before the next shot / until the magazine is full again
+    pub cooldown: f32,
+    pub reloading: f32,
+    // seconds the trigger has been held for charging weapons
+    pub charge: f32,
+    // seconds until the color can be changed again
+    pub switch_lock: f32,
+}
+
+// which player a weapon, crosshair or shot belongs to
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shooter {
+    One,
+    Two,
+}
+
+// one player's trigger this frame
+#[derive(Default, Clone, Copy)]
+pub struct Trigger {
+    pub just_pressed: bool,
+    pub held: bool,
+    pub just_released: bool,
+}
+
+// the trigger was pulled and the weapon was ready to fire
+#[derive(Event)]
+pub struct FireEvent(pub Shooter);
+
+// how a fired shot went, for anything keeping stats
+#[derive(Event)]
+pub struct ShotResult {
+    pub shooter: Shooter,
+    // game seconds each enemy the shot killed had been alive
+    pub kills: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponMode {
+    // stops at the first thing it hits
+    Single,
+    // carries on through everything along the ray
+    Pierce,
+    // a cone of rays
+    Spread,
+    // also kills matching enemies around the impact
+    Splash,
+}
 
 #[derive(Component)]
 pub struct PlayerMarker;
@@ -38,7 +175,51 @@ pub struct PlayerMarker;
 #[derive(Resource)]
 pub struct KillCount(pub usize);
 
-fn init_player(mut commands: Commands) {
+impl Shooter {
+    pub const ALL: [Shooter; 2] = [Shooter::One, Shooter::Two];
+
+    pub fn index(self) -> usize {
+        match self {
+            Shooter::One => 0,
+            Shooter::Two => 1,
+        }
+    }
+}
+
+impl PlayerWeapon {
+    pub fn new(weapon: usize, color: Kulay) -> Self {
+        Self {
+            weapon,
+            color,
+            ammo: WEAPONS[weapon].magazine,
+            cooldown: 0.,
+            reloading: 0.,
+            charge: 0.,
+            switch_lock: 0.,
+        }
+    }
+
+    pub fn spec(&self) -> &'static WeaponSpec {
+        &WEAPONS[self.weapon]
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown <= 0. && self.reloading <= 0. && self.ammo > 0
+    }
+
+    pub fn start_reload(&mut self) {
+        if self.reloading <= 0. && self.ammo < self.spec().magazine {
+            self.reloading = self.spec().reload;
+            self.charge = 0.;
+        }
+    }
+
+    pub fn refill(&mut self) {
+        *self = Self::new(self.weapon, self.color);
+    }
+}
+
+fn init_player(mut commands: Commands, settings: Res<Settings>) {
     let player_collider = commands
         .spawn(Collider::cuboid(10., 10., 1.))
         .insert(PlayerMarker)
@@ -46,42 +227,207 @@ fn init_player(mut commands: Commands) {
         .id();
     let cam = Camera3dBundle {
         transform: Transform::from_xyz(0., 4.6, 0.),
+        projection: Projection::Perspective(PerspectiveProjection {
+            fov: settings.fov.to_radians(),
+            ..default()
+        }),
         ..Default::default()
     };
     let fog = FogSettings {
         color: Color::srgb(0.25, 0.25, 0.25),
         falloff: FogFalloff::Linear {
-            start: 15.0,
-            end: 100.0,
+            start: settings.fog_start,
+            end: settings.fog_end,
         },
         ..default()
     };
     commands
-        .spawn((CamMarker, cam, fog))
+        .spawn((
+            CamMarker,
+            cam,
+            fog,
+            SpatialListener::new(4.),
+            PlayerWeapon::new(0, Kulay::Asul),
+            Shooter::One,
+        ))
         .add_child(player_collider);
 }
 
-fn switch_weapon_system(keys: Res<ButtonInput<KeyCode>>, mut weapon: ResMut<PlayerWeapon>) {
-    if keys.just_pressed(KeyCode::Digit1) {
-        weapon.0 = Kulay::Asul;
-    } else if keys.just_pressed(KeyCode::Digit2) {
-        weapon.0 = Kulay::Pula;
+fn switch_weapon_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut gestures: EventReader<GestureAction>,
+    mut weapon: Query<(&mut PlayerWeapon, &Shooter)>,
+    second: Res<SecondPlayer>,
+    game_mode: Res<GameMode>,
+    kill_count: Res<KillCount>,
+    wave_config: Res<WaveConfig>,
+    daily_run: Res<DailyRun>,
+    time: Res<Time>,
+) {
+    // in co-op the tracker is player two's
+    let gesture_color = gestures
+        .read()
+        .filter_map(|action| match action {
+            GestureAction::SelectWeapon(color) => Some(*color),
+            _ => None,
+        })
+        .filter(|_| game_mode.players() == 1)
+        .last();
+    let key_color = [
+        (KeyCode::Digit1, Kulay::Asul),
+        (KeyCode::Digit2, Kulay::Pula),
+        (KeyCode::Digit3, Kulay::Dilaw),
+    ]
+    .into_iter()
+    .find(|(key, _)| keys.just_pressed(*key))
+    .map(|(_, color)| color);
+    let locking =
+        daily_run.has(Modifier::SwitchLock) && kill_count.is_changed() && kill_count.0 > 0;
+    for (mut weapon, shooter) in weapon.iter_mut() {
+        // R is taken by the debug reset
+        let (picked, cycle_weapon, reload) = match shooter {
+            Shooter::One => (
+                key_color.or(gesture_color),
+                keys.just_pressed(KeyCode::KeyQ),
+                keys.just_pressed(KeyCode::KeyE),
+            ),
+            Shooter::Two => (second.color, second.cycle_weapon, second.reload),
+        };
+        // only the colors that can spawn this run
+        let picked = picked.filter(|color| wave_config.colors.contains(color));
+        if locking {
+            weapon.switch_lock = SWITCH_LOCK_SECS;
+        }
+        if weapon.switch_lock > 0. {
+            weapon.switch_lock = (weapon.switch_lock - time.delta_seconds()).max(0.);
+        }
+        if let Some(color) = picked.filter(|_| weapon.switch_lock <= 0.) {
+            weapon.color = color;
+        }
+        if cycle_weapon {
+            // a fresh gun comes out loaded, but still has to cycle before firing
+            let next = (weapon.weapon + 1) % WEAPONS.len();
+            let switch_lock = weapon.switch_lock;
+            *weapon = PlayerWeapon::new(next, weapon.color);
+            weapon.cooldown = weapon.spec().cooldown;
+            weapon.switch_lock = switch_lock;
+        }
+        if reload {
+            weapon.start_reload();
+        }
+    }
+}
+
+fn weapon_trigger_system(
+    mouse: Res<ButtonInput<MouseButton>>,
+    gestures: Res<Gestures>,
+    mut gesture_actions: EventReader<GestureAction>,
+    second: Res<SecondPlayer>,
+    game_mode: Res<GameMode>,
+    mut weapon: Query<(&mut PlayerWeapon, &Shooter)>,
+    mut fire: EventWriter<FireEvent>,
+    time: Res<Time>,
+) {
+    // a pinch works the trigger just like the left button, unless player two has the tracker
+    let solo = game_mode.players() == 1;
+    let (mut pinched, mut unpinched) = (false, false);
+    for action in gesture_actions.read() {
+        match action {
+            GestureAction::Fire => pinched = solo,
+            GestureAction::CeaseFire => unpinched = solo,
+            GestureAction::SelectWeapon(_) => {}
+        }
+    }
+    let first = Trigger {
+        just_pressed: mouse.just_pressed(MouseButton::Left) || pinched,
+        held: mouse.pressed(MouseButton::Left) || (solo && gestures.pinch.on),
+        just_released: mouse.just_released(MouseButton::Left) || unpinched,
+    };
+
+    for (mut weapon, shooter) in weapon.iter_mut() {
+        let trigger = match shooter {
+            Shooter::One => first,
+            Shooter::Two => second.trigger,
+        };
+        let dt = time.delta_seconds();
+        weapon.cooldown = (weapon.cooldown - dt).max(0.);
+        if weapon.reloading > 0. {
+            weapon.reloading -= dt;
+            if weapon.reloading <= 0. {
+                weapon.reloading = 0.;
+                weapon.ammo = weapon.spec().magazine;
+            }
+            continue;
+        }
+
+        let pulled = match weapon.spec().charge {
+            None => trigger.just_pressed,
+            Some(charge_secs) => {
+                if trigger.held && weapon.is_ready() {
+                    weapon.charge += dt;
+                }
+                let charged = weapon.charge >= charge_secs;
+                if trigger.just_released {
+                    weapon.charge = 0.;
+                    charged
+                } else {
+                    false
+                }
+            }
+        };
+        if !pulled {
+            continue;
+        }
+        if weapon.ammo == 0 {
+            weapon.start_reload();
+            continue;
+        }
+        if !weapon.is_ready() {
+            continue;
+        }
+
+        weapon.ammo -= 1;
+        weapon.cooldown = weapon.spec().cooldown;
+        fire.send(FireEvent(*shooter));
+        if weapon.ammo == 0 {
+            weapon.start_reload();
+        }
+    }
+}
+
+// the center ray plus, for a spread, a ring of pellets around it
+fn shot_rays(mode: WeaponMode, ray: Ray3d, cam_transform: &GlobalTransform) -> Vec<Ray3d> {
+    let mut rays = vec![ray];
+    if mode != WeaponMode::Spread {
+        return rays;
+    }
+    let (right, up) = (*cam_transform.right(), *cam_transform.up());
+    for i in 0..SPREAD_PELLETS {
+        let angle = i as f32 / SPREAD_PELLETS as f32 * TAU;
+        let offset = (right * angle.cos() + up * angle.sin()) * SPREAD_ANGLE.tan();
+        let direction = Dir3::new(*ray.direction + offset).unwrap_or(ray.direction);
+        rays.push(Ray3d {
+            origin: ray.origin,
+            direction,
+        });
     }
+    rays
 }
 
-fn player_movement_system(
+pub fn player_movement_system(
     mut mouse_evt: EventReader<MouseMotion>,
     mut cam: Query<&mut Transform, With<CamMarker>>,
     keys: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
+    settings: Res<Settings>,
 ) {
     let mut cam = cam.single_mut();
     for mouse_motion in mouse_evt.read() {
-        let delta_yaw = -mouse_motion.delta.x * 0.0005;
-        let delta_pitch = -mouse_motion.delta.y * 0.0005;
+        let delta_yaw = -mouse_motion.delta.x * settings.mouse_sensitivity;
+        let delta_pitch = -mouse_motion.delta.y * settings.mouse_sensitivity;
         let (yaw, pitch, roll) = cam.rotation.to_euler(EulerRot::YXZ);
-        let yaw = (yaw + delta_yaw).clamp(-0.1, 0.1);
-        let pitch = (pitch + delta_pitch).clamp(-0.1, 0.1);
+        let yaw = (yaw + delta_yaw).clamp(-LOOK_CLAMP, LOOK_CLAMP);
+        let pitch = (pitch + delta_pitch).clamp(-LOOK_CLAMP, LOOK_CLAMP);
         cam.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
     }
 
@@ -100,57 +446,209 @@ fn player_movement_system(
     }
 }
 
+// everything a shot reports back to the rest of the game
+#[derive(SystemParam)]
+struct ShotEvents<'w> {
+    play_sfx: EventWriter<'w, PlaySfxAt>,
+    effects: EventWriter<'w, EffectEvent>,
+    popups: EventWriter<'w, ScorePopupEvent>,
+    powerups: EventWriter<'w, PowerUpCollected>,
+    results: EventWriter<'w, ShotResult>,
+    damage: EventWriter<'w, DamageEvent>,
+}
+
+// the scores and combo a shot plays into
+#[derive(SystemParam)]
+struct ShotScore<'w> {
+    points: Scores<'w>,
+    combo: ResMut<'w, Combo>,
+}
+
 fn player_shoot_system(
     mut commands: Commands,
     cam: Query<(&GlobalTransform, &Camera), With<CamMarker>>,
     mut enemy_state: ResMut<EnemyState>,
     rapier_context: Res<RapierContext>,
-    mut scoreboard: ResMut<Score>,
+    mut score: ShotScore,
     mut kill_count: ResMut<KillCount>,
-    enemies: Query<&Kulay>,
-    player_weapon: Res<PlayerWeapon>,
-    win: Query<&Window, With<PrimaryWindow>>,
-    asset_server: Res<AssetServer>,
+    enemies: Query<(Entity, &Kulay, &GlobalTransform, &SpawnedAt)>,
+    powerups: Query<&PowerUp>,
+    player_weapon: Query<(&PlayerWeapon, &Shooter)>,
+    active_powerups: Res<ActivePowerUps>,
+    mut aim_assist: AimAssist,
+    mut fire: EventReader<FireEvent>,
+    mut events: ShotEvents,
+    game_mode: Res<GameMode>,
+    mut health: ResMut<PlayerHealth>,
 ) {
-    let (cam_transform, cam) = cam.single();
-
-    let Some(cursor_position) = win.single().cursor_position() else {
+    let Ok((cam_transform, cam)) = cam.get_single() else {
         return;
     };
+    let filter =
+        QueryFilter::default().groups(CollisionGroups::new(Group::default(), Group::GROUP_2));
+    let piercing_powerup = active_powerups.is_active(PowerUpKind::Piercing);
+    let wildcard = active_powerups.is_active(PowerUpKind::Wildcard);
+    // both players can fire in the same frame; whatever the first shot
+    // touched is gone before the second one lands
+    let mut seen = HashSet::new();
 
-    let Some(ray) = cam.viewport_to_world(cam_transform, cursor_position) else {
-        return;
-    };
+    for &FireEvent(shooter) in fire.read() {
+        let Some((player_weapon, _)) = player_weapon.iter().find(|(_, s)| **s == shooter) else {
+            continue;
+        };
 
-    let ray_context = rapier_context.cast_ray(
-        ray.origin,
-        *ray.direction,
-        255.,
-        false,
-        QueryFilter::default().groups(CollisionGroups::new(Group::default(), Group::GROUP_2)),
-    );
+        let targets = enemies
+            .iter()
+            .map(|(_, color, transform, _)| (transform.translation(), *color));
+        let Some(aim) =
+            aim_assist.shot_target(shooter, cam, cam_transform, targets, player_weapon.color)
+        else {
+            continue;
+        };
 
-    let Some((entity, _)) = ray_context else {
-        return;
-    };
+        let Some(ray) = cam.viewport_to_world(cam_transform, aim) else {
+            continue;
+        };
 
-    if let Ok(color) = enemies.get(entity) {
-        if *color == player_weapon.0 {
-            eliminate_enemy(&mut commands, entity, &mut enemy_state);
-            scoreboard.0 += 100;
-            kill_count.0 += 1;
-            commands.spawn(AudioBundle {
-                source: asset_server.load("attack.ogg"),
-                settings: PlaybackSettings::DESPAWN,
-            });
-        } else {
-            scoreboard.0 -= 100;
-            commands.spawn(AudioBundle {
-                source: asset_server.load("damage.ogg"),
-                settings: PlaybackSettings::DESPAWN,
+        let mode = player_weapon.spec().mode;
+        let piercing = mode == WeaponMode::Pierce || piercing_powerup;
+
+        // every entity the shot touched with where it touched it, nearest first per ray
+        let mut hits: Vec<(Entity, Vec3)> = Vec::new();
+        for ray in shot_rays(mode, ray, cam_transform) {
+            let mut ray_hits: Vec<(Entity, f32)> = Vec::new();
+            if piercing {
+                rapier_context.intersections_with_ray(
+                    ray.origin,
+                    *ray.direction,
+                    SHOT_RANGE,
+                    false,
+                    filter,
+                    |entity, intersection| {
+                        ray_hits.push((entity, intersection.time_of_impact));
+                        true
+                    },
+                );
+                ray_hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+                // it only takes out matching enemies and passes through the rest
+                ray_hits.retain(|(entity, _)| {
+                    enemies.get(*entity).map_or(true, |(_, color, _, _)| {
+                        *color == player_weapon.color || wildcard
+                    })
+                });
+            } else {
+                ray_hits.extend(rapier_context.cast_ray(
+                    ray.origin,
+                    *ray.direction,
+                    SHOT_RANGE,
+                    false,
+                    filter,
+                ));
+            }
+
+            // a piercing shot keeps going past whatever it hits
+            let toi = match ray_hits.first() {
+                Some((_, toi)) if !piercing => *toi,
+                _ => SHOT_RANGE,
+            };
+            events.effects.send(EffectEvent::Tracer {
+                from: ray.origin,
+                to: ray.get_point(toi),
             });
+
+            if mode == WeaponMode::Splash {
+                if let Some((_, toi)) = ray_hits.first() {
+                    let impact = ray.get_point(*toi);
+                    let splashed = enemies.iter().filter(|(_, color, transform, _)| {
+                        (**color == player_weapon.color || wildcard)
+                            && transform.translation().distance(impact) <= SPLASH_RADIUS
+                    });
+                    hits.extend(
+                        splashed.map(|(entity, _, transform, _)| (entity, transform.translation())),
+                    );
+                }
+            }
+            hits.extend(
+                ray_hits
+                    .into_iter()
+                    .map(|(entity, toi)| (entity, ray.get_point(toi))),
+            );
+        }
+
+        // spread pellets and splash can land on the same entity more than once
+        hits.retain(|(entity, _)| seen.insert(*entity));
+
+        if hits.is_empty() && game_mode.strict() {
+            score.combo.streak /= 2;
+        }
+
+        // however many pellets land on the wrong color, the shot only costs once
+        let mut deflected = false;
+        let mut kills = Vec::new();
+        for (entity, pos) in hits {
+            if let Ok(powerup) = powerups.get(entity) {
+                events.powerups.send(PowerUpCollected {
+                    orb: entity,
+                    kind: powerup.0,
+                    pos,
+                    shooter,
+                });
+                continue;
+            }
+
+            let Ok((_, color, enemy_transform, spawned_at)) = enemies.get(entity) else {
+                score.points.add(shooter, -100);
+                events.popups.send(ScorePopupEvent {
+                    points: -100,
+                    multiplier: 1,
+                    pos: None,
+                });
+                continue;
+            };
+
+            let enemy_pos = enemy_transform.translation();
+            if *color == player_weapon.color || wildcard {
+                eliminate_enemy(&mut commands, entity, &mut enemy_state);
+                kills.push(enemy_state.elapsed - spawned_at.0);
+                let multiplier = score.combo.multiplier();
+                score.points.add(shooter, 100 * multiplier as i32);
+                score.combo.streak += 1;
+                kill_count.0 += 1;
+                events.popups.send(ScorePopupEvent {
+                    points: 100 * multiplier as i32,
+                    multiplier,
+                    pos: Some(enemy_pos),
+                });
+                events.play_sfx.send(PlaySfxAt(SfxId::Attack, enemy_pos));
+                events.effects.send(EffectEvent::Burst {
+                    pos: enemy_pos,
+                    color: *color,
+                });
+                events
+                    .effects
+                    .send(EffectEvent::HitMarker(HitMarker::Kill, shooter));
+            } else {
+                if !deflected {
+                    score.points.add(shooter, -100);
+                    score.combo.streak = 0;
+                    if game_mode.strict() {
+                        health.0 = health.0.saturating_sub(1);
+                        events.damage.send(DamageEvent);
+                    }
+                    events.popups.send(ScorePopupEvent {
+                        points: -100,
+                        multiplier: 1,
+                        pos: Some(enemy_pos),
+                    });
+                }
+                deflected = true;
+                events.play_sfx.send(PlaySfxAt(SfxId::Damage, enemy_pos));
+                events.effects.send(EffectEvent::Deflect { pos });
+                events
+                    .effects
+                    .send(EffectEvent::HitMarker(HitMarker::Deflect, shooter));
+            }
         }
-    } else {
-        scoreboard.0 -= 100;
+        events.results.send(ShotResult { shooter, kills });
     }
 }
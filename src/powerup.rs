@@ -0,0 +1,319 @@
+use crate::audio::{PlaySfxAt, SfxId};
+use crate::effects::{EffectEvent, HitMarker};
+use crate::enemy::{SpawnRng, WaveConfig};
+use crate::globals::{GameSpeed, GameState, PauseState};
+use crate::hud::{clean_hud_system, HudEntities};
+use crate::player::{MaxHealth, PlayerHealth, PlayerShield, Shooter};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{Collider, CollisionGroups, Group, Sensor};
+use rand::Rng;
+use std::collections::HashMap;
+
+const ORB_RADIUS: f32 = 0.6;
+const ORB_SPEED: f32 = 6.;
+// orbs that drift past the player are gone
+const ORB_DESPAWN_Z: f32 = 5.;
+const SLOW_MOTION_SPEED: f32 = 0.5;
+// each gap between orbs is the average gap scaled by somewhere in this range
+const SPAWN_JITTER: (f32, f32) = (0.5, 1.5);
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActivePowerUps::default())
+            .insert_resource(PowerUpTimers::default())
+            .add_event::<PowerUpCollected>()
+            .add_systems(Startup, init_powerup_assets)
+            .add_systems(
+                OnEnter(GameState::InGame),
+                init_powerup_hud.after(clean_hud_system),
+            )
+            .add_systems(OnExit(GameState::InGame), clear_powerups_system)
+            .add_systems(
+                Update,
+                (
+                    powerup_spawn_system,
+                    powerup_movement_system,
+                    collect_powerup_system,
+                    expire_powerup_system,
+                    refresh_powerup_hud_system,
+                )
+                    .chain()
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    Heart,
+    Shield,
+    SlowMotion,
+    Wildcard,
+    Piercing,
+}
+
+#[derive(Component)]
+pub struct PowerUp(pub PowerUpKind);
+
+// sent by whoever shot the orb, applied and cleaned up here
+#[derive(Event)]
+pub struct PowerUpCollected {
+    pub orb: Entity,
+    pub kind: PowerUpKind,
+    pub pos: Vec3,
+    pub shooter: Shooter,
+}
+
+// timed power-ups and how long they have left, in real seconds
+#[derive(Resource, Default)]
+pub struct ActivePowerUps(HashMap<PowerUpKind, Timer>);
+
+// game seconds until the next orb of each kind, rolled from `SpawnRng` so a
+// seeded run drops the same power-ups at the same times
+#[derive(Resource, Default)]
+struct PowerUpTimers(HashMap<PowerUpKind, f32>);
+
+#[derive(Component)]
+struct PowerUpHudMarker;
+
+#[derive(Resource)]
+struct PowerUpAssets {
+    orb: Handle<Mesh>,
+    materials: HashMap<PowerUpKind, Handle<StandardMaterial>>,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 5] = [
+        PowerUpKind::Heart,
+        PowerUpKind::Shield,
+        PowerUpKind::SlowMotion,
+        PowerUpKind::Wildcard,
+        PowerUpKind::Piercing,
+    ];
+
+    // 0 means the power-up is used up the moment it's collected
+    pub fn duration(self) -> f32 {
+        match self {
+            PowerUpKind::Heart => 0.,
+            PowerUpKind::Shield => 10.,
+            PowerUpKind::SlowMotion => 5.,
+            PowerUpKind::Wildcard => 8.,
+            PowerUpKind::Piercing => 8.,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUpKind::Heart => "Heart",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::SlowMotion => "Slow Motion",
+            PowerUpKind::Wildcard => "Wildcard",
+            PowerUpKind::Piercing => "Piercing",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PowerUpKind::Heart => Color::srgb(1., 0.35, 0.5),
+            PowerUpKind::Shield => Color::srgb(0.3, 0.8, 1.),
+            PowerUpKind::SlowMotion => Color::srgb(0.6, 1., 0.4),
+            PowerUpKind::Wildcard => Color::srgb(1., 0.85, 0.2),
+            PowerUpKind::Piercing => Color::srgb(0.8, 0.5, 1.),
+        }
+    }
+}
+
+impl ActivePowerUps {
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.0.contains_key(&kind)
+    }
+}
+
+fn init_powerup_assets(
+    mut commands: Commands,
+    mut mesh: ResMut<Assets<Mesh>>,
+    mut material: ResMut<Assets<StandardMaterial>>,
+) {
+    let materials = PowerUpKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let color = kind.color();
+            let handle = material.add(StandardMaterial {
+                base_color: color,
+                emissive: color.into(),
+                ..default()
+            });
+            (kind, handle)
+        })
+        .collect();
+    commands.insert_resource(PowerUpAssets {
+        orb: mesh.add(Sphere { radius: ORB_RADIUS }),
+        materials,
+    });
+}
+
+fn powerup_spawn_system(
+    mut commands: Commands,
+    wave_config: Res<WaveConfig>,
+    assets: Res<PowerUpAssets>,
+    mut timers: ResMut<PowerUpTimers>,
+    mut rng: ResMut<SpawnRng>,
+    time: Res<Time>,
+) {
+    let rng = &mut rng.powerups;
+    for &(kind, per_minute) in &wave_config.powerups_per_minute {
+        if per_minute <= 0. {
+            continue;
+        }
+        let mut roll_gap = || 60. / per_minute * rng.gen_range(SPAWN_JITTER.0..=SPAWN_JITTER.1);
+        let timer = timers.0.entry(kind).or_insert_with(&mut roll_gap);
+        *timer -= time.delta_seconds();
+        if *timer > 0. {
+            continue;
+        }
+        *timer = roll_gap();
+        let pos = Vec3::new(rng.gen_range(-9.0..=9.0), rng.gen_range(3.0..=8.0), -50.);
+        commands
+            .spawn((
+                PbrBundle {
+                    mesh: assets.orb.clone(),
+                    material: assets.materials[&kind].clone(),
+                    transform: Transform::from_translation(pos),
+                    ..default()
+                },
+                PowerUp(kind),
+            ))
+            .insert(Sensor)
+            .insert(Collider::ball(ORB_RADIUS))
+            // shootable like an enemy, but never touches the player collider
+            .insert(CollisionGroups::new(Group::GROUP_2, Group::GROUP_3));
+    }
+}
+
+fn powerup_movement_system(
+    mut commands: Commands,
+    mut orbs: Query<(Entity, &mut Transform), With<PowerUp>>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform) in orbs.iter_mut() {
+        transform.translation.z += ORB_SPEED * time.delta_seconds();
+        if transform.translation.z > ORB_DESPAWN_Z {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn collect_powerup_system(
+    mut commands: Commands,
+    mut collected: EventReader<PowerUpCollected>,
+    mut active: ResMut<ActivePowerUps>,
+    mut player_health: ResMut<PlayerHealth>,
+    max_health: Res<MaxHealth>,
+    mut shield: ResMut<PlayerShield>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut effects: EventWriter<EffectEvent>,
+    mut play_sfx: EventWriter<PlaySfxAt>,
+) {
+    for PowerUpCollected {
+        orb,
+        kind,
+        pos,
+        shooter,
+    } in collected.read()
+    {
+        if let Some(orb) = commands.get_entity(*orb) {
+            orb.despawn_recursive();
+        }
+        effects.send(EffectEvent::Pickup { pos: *pos });
+        effects.send(EffectEvent::HitMarker(HitMarker::Kill, *shooter));
+        play_sfx.send(PlaySfxAt(SfxId::Attack, *pos));
+
+        match kind {
+            PowerUpKind::Heart => player_health.0 = (player_health.0 + 1).min(max_health.0),
+            // one hit's worth, another pickup only restarts the timer
+            PowerUpKind::Shield => shield.0 = 1,
+            PowerUpKind::SlowMotion => game_speed.0 = SLOW_MOTION_SPEED,
+            PowerUpKind::Wildcard | PowerUpKind::Piercing => (),
+        }
+        if kind.duration() > 0. {
+            // collecting one that's already running just restarts it
+            active
+                .0
+                .insert(*kind, Timer::from_seconds(kind.duration(), TimerMode::Once));
+        }
+    }
+}
+
+fn expire_powerup_system(
+    mut active: ResMut<ActivePowerUps>,
+    mut shield: ResMut<PlayerShield>,
+    mut game_speed: ResMut<GameSpeed>,
+    time: Res<Time<Real>>,
+) {
+    active.0.retain(|kind, timer| {
+        // a shield that soaked up its hit is done, whatever the timer says
+        let spent = *kind == PowerUpKind::Shield && shield.0 == 0;
+        if !spent && !timer.tick(time.delta()).finished() {
+            return true;
+        }
+        match kind {
+            PowerUpKind::Shield => shield.0 = 0,
+            PowerUpKind::SlowMotion => game_speed.0 = 1.,
+            _ => (),
+        }
+        false
+    });
+}
+
+fn clear_powerups_system(
+    mut commands: Commands,
+    orbs: Query<Entity, With<PowerUp>>,
+    mut active: ResMut<ActivePowerUps>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut timers: ResMut<PowerUpTimers>,
+) {
+    for orb in &orbs {
+        commands.entity(orb).despawn_recursive();
+    }
+    active.0.clear();
+    timers.0.clear();
+    game_speed.0 = 1.;
+}
+
+fn init_powerup_hud(mut commands: Commands, mut hud_entities: ResMut<HudEntities>) {
+    let list = TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(50.),
+            top: Val::Px(50.),
+            ..default()
+        },
+        ..default()
+    };
+    let list = commands.spawn((list, PowerUpHudMarker)).id();
+    hud_entities.0.push(list);
+}
+
+fn refresh_powerup_hud_system(
+    active: Res<ActivePowerUps>,
+    mut hud: Query<&mut Text, With<PowerUpHudMarker>>,
+) {
+    for mut text in hud.iter_mut() {
+        text.sections = PowerUpKind::ALL
+            .into_iter()
+            .filter_map(|kind| Some((kind, active.0.get(&kind)?)))
+            .map(|(kind, timer)| {
+                TextSection::new(
+                    format!("{} {:.0}s\n", kind.label(), timer.remaining_secs().ceil()),
+                    TextStyle {
+                        font_size: 28.,
+                        color: kind.color(),
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,272 @@
+use crate::enemy::{eliminate_enemy, Archetype, Enemy, EnemyState, WaveConfig};
+use crate::globals::{GameState, Kulay, PauseState};
+use crate::hud::HudEntities;
+use crate::mode::{start_round_system, GameMode};
+use crate::player::ShotResult;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+const TARGET_COUNTS: [usize; 4] = [1, 3, 6, 10];
+const SPEEDS: [(&str, f32); 3] = [("In Place", 0.), ("Slow", 0.3), ("Normal", 1.)];
+// how soon a shot target is replaced
+const SPAWN_RATES: [(&str, Option<f32>); 3] = [
+    ("Instant", None),
+    ("Every 1s", Some(1.)),
+    ("Every 3s", Some(3.)),
+];
+const ARCHETYPES: [(&str, &[Archetype]); 4] = [
+    ("Grunts", &[Archetype::Grunt]),
+    ("Runners", &[Archetype::Runner]),
+    ("Weavers", &[Archetype::Weaver]),
+    ("Mixed", &Archetype::ALL),
+];
+// bars per graph, oldest on the left
+const GRAPH_SAMPLES: usize = 24;
+const GRAPH_WIDTH: f32 = 240.;
+const GRAPH_HEIGHT: f32 = 60.;
+// accuracy is plotted as the hit rate over this many shots
+const ACCURACY_WINDOW: usize = 10;
+// seconds at the top of the reaction graph
+const REACTION_SCALE: f32 = 3.;
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PracticeConfig::default())
+            .insert_resource(PracticeStats::default())
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (init_practice, init_practice_hud)
+                    .chain()
+                    .after(start_round_system)
+                    .run_if(resource_equals(GameMode::Practice)),
+            )
+            .add_systems(
+                Update,
+                (
+                    practice_toggle_system,
+                    record_practice_stats_system,
+                    refresh_practice_hud_system,
+                )
+                    .chain()
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(resource_equals(GameMode::Practice)),
+            );
+    }
+}
+
+// kept between practice runs, indices into the option tables above
+#[derive(Resource)]
+struct PracticeConfig {
+    targets: usize,
+    spawn_rate: usize,
+    speed: usize,
+    colors: usize,
+    archetypes: usize,
+}
+
+#[derive(Resource, Default)]
+struct PracticeStats {
+    // whether each recent shot killed anything
+    shots: VecDeque<bool>,
+    accuracy: VecDeque<f32>,
+    reactions: VecDeque<f32>,
+}
+
+#[derive(Component)]
+struct PracticeText;
+
+#[derive(Clone, Copy)]
+enum Graph {
+    Accuracy,
+    Reaction,
+}
+
+#[derive(Component)]
+struct GraphBar {
+    graph: Graph,
+    index: usize,
+}
+
+impl Default for PracticeConfig {
+    fn default() -> Self {
+        Self {
+            targets: 1,
+            spawn_rate: 0,
+            speed: 0,
+            colors: 2,
+            archetypes: 0,
+        }
+    }
+}
+
+impl PracticeConfig {
+    // practice goes through the normal spawner, only with its knobs turned
+    fn apply(&self, wave_config: &mut WaveConfig) {
+        wave_config.enemy_count = Some(TARGET_COUNTS[self.targets]);
+        wave_config.spawn_interval = SPAWN_RATES[self.spawn_rate].1;
+        wave_config.speed_scale = SPEEDS[self.speed].1;
+        wave_config.colors = Kulay::ALL[..self.colors].to_vec();
+        wave_config.archetypes = ARCHETYPES[self.archetypes].1.to_vec();
+        wave_config.powerups_per_minute.clear();
+    }
+}
+
+fn push_capped<T>(samples: &mut VecDeque<T>, sample: T, cap: usize) {
+    samples.push_back(sample);
+    if samples.len() > cap {
+        samples.pop_front();
+    }
+}
+
+fn init_practice(
+    mut stats: ResMut<PracticeStats>,
+    mut wave_config: ResMut<WaveConfig>,
+    config: Res<PracticeConfig>,
+) {
+    *stats = PracticeStats::default();
+    config.apply(&mut wave_config);
+}
+
+fn init_practice_hud(mut commands: Commands, mut hud_entities: ResMut<HudEntities>) {
+    let panel = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(50.),
+            top: Val::Percent(30.),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        ..default()
+    };
+    let panel = commands.spawn(panel).id();
+    hud_entities.0.push(panel);
+
+    let text = commands.spawn((TextBundle::default(), PracticeText)).id();
+    commands.entity(panel).add_child(text);
+
+    for graph in [Graph::Accuracy, Graph::Reaction] {
+        let frame = NodeBundle {
+            style: Style {
+                width: Val::Px(GRAPH_WIDTH),
+                height: Val::Px(GRAPH_HEIGHT),
+                display: Display::Flex,
+                align_items: AlignItems::End,
+                column_gap: Val::Px(2.),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.4)),
+            ..default()
+        };
+        let frame = commands.spawn(frame).id();
+        commands.entity(panel).add_child(frame);
+        for index in 0..GRAPH_SAMPLES {
+            let bar = NodeBundle {
+                style: Style {
+                    flex_grow: 1.,
+                    height: Val::Percent(0.),
+                    ..default()
+                },
+                ..default()
+            };
+            let bar = commands.spawn((bar, GraphBar { graph, index })).id();
+            commands.entity(frame).add_child(bar);
+        }
+    }
+}
+
+fn practice_toggle_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<PracticeConfig>,
+    mut wave_config: ResMut<WaveConfig>,
+    mut enemy_state: ResMut<EnemyState>,
+    enemies: Query<Entity, With<Enemy>>,
+) {
+    if keys.just_pressed(KeyCode::KeyZ) {
+        config.targets = (config.targets + 1) % TARGET_COUNTS.len();
+    } else if keys.just_pressed(KeyCode::KeyX) {
+        config.spawn_rate = (config.spawn_rate + 1) % SPAWN_RATES.len();
+    } else if keys.just_pressed(KeyCode::KeyC) {
+        config.speed = (config.speed + 1) % SPEEDS.len();
+    } else if keys.just_pressed(KeyCode::KeyV) {
+        config.colors = if config.colors == 2 { 3 } else { 2 };
+    } else if keys.just_pressed(KeyCode::KeyB) {
+        config.archetypes = (config.archetypes + 1) % ARCHETYPES.len();
+    } else {
+        return;
+    }
+    config.apply(&mut wave_config);
+    // clear the field so the new settings show up right away
+    for enemy in &enemies {
+        eliminate_enemy(&mut commands, enemy, &mut enemy_state);
+    }
+}
+
+fn record_practice_stats_system(
+    mut stats: ResMut<PracticeStats>,
+    mut results: EventReader<ShotResult>,
+) {
+    for result in results.read() {
+        push_capped(&mut stats.shots, !result.kills.is_empty(), ACCURACY_WINDOW);
+        let accuracy =
+            stats.shots.iter().filter(|hit| **hit).count() as f32 / stats.shots.len() as f32;
+        push_capped(&mut stats.accuracy, accuracy, GRAPH_SAMPLES);
+        for reaction in &result.kills {
+            push_capped(&mut stats.reactions, *reaction, GRAPH_SAMPLES);
+        }
+    }
+}
+
+fn refresh_practice_hud_system(
+    config: Res<PracticeConfig>,
+    stats: Res<PracticeStats>,
+    mut text: Query<&mut Text, With<PracticeText>>,
+    mut bars: Query<(&mut Style, &mut BackgroundColor, &GraphBar)>,
+) {
+    if !config.is_changed() && !stats.is_changed() {
+        return;
+    }
+
+    let accuracy = stats.accuracy.back().map_or("-".to_string(), |accuracy| {
+        format!("{:.0}%", accuracy * 100.)
+    });
+    let reaction = match stats.reactions.len() {
+        0 => "-".to_string(),
+        count => format!("{:.2}s", stats.reactions.iter().sum::<f32>() / count as f32),
+    };
+    for mut text in text.iter_mut() {
+        *text = Text::from_section(
+            format!(
+                "Practice Range\n[Z] Targets: {}\n[X] Spawn: {}\n[C] Speed: {}\n[V] Colors: {}\n[B] Enemies: {}\n\nAccuracy {}\nReaction {}",
+                TARGET_COUNTS[config.targets],
+                SPAWN_RATES[config.spawn_rate].0,
+                SPEEDS[config.speed].0,
+                config.colors,
+                ARCHETYPES[config.archetypes].0,
+                accuracy,
+                reaction,
+            ),
+            TextStyle {
+                font_size: 24.,
+                ..default()
+            },
+        );
+    }
+
+    for (mut style, mut background, bar) in bars.iter_mut() {
+        let (samples, color) = match bar.graph {
+            Graph::Accuracy => (&stats.accuracy, Color::srgb(0.4, 0.9, 0.5)),
+            Graph::Reaction => (&stats.reactions, Color::srgb(0.42, 0.71, 0.85)),
+        };
+        let value = samples.get(bar.index).map_or(0., |sample| match bar.graph {
+            Graph::Accuracy => *sample,
+            Graph::Reaction => (*sample / REACTION_SCALE).min(1.),
+        });
+        style.height = Val::Percent(value * 100.);
+        *background = BackgroundColor(color);
+    }
+}
@@ -0,0 +1,464 @@
+use crate::aim::AimAssistUsed;
+use crate::daily::DailyRun;
+use crate::globals::GameState;
+use crate::hud::Score;
+use crate::mode::GameMode;
+use crate::player::KillCount;
+use crate::settings::Settings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// bump this whenever the layout changes in a way `#[serde(default)]` can't
+// paper over, keeping the old layout around for `migrate` to lift
+pub const SAVE_VERSION: u32 = 2;
+const SAVE_FILE: &str = "save.ron";
+const HIGH_SCORE_LIMIT: usize = 10;
+const RUN_HISTORY_LIMIT: usize = 50;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let path = save_path();
+        let (save, writable) = load_save(&path);
+
+        app.insert_resource(save.settings)
+            .insert_resource(save.progress)
+            .insert_resource(SavePath(writable.then_some(path)))
+            .add_event::<SaveEvent>()
+            .add_systems(OnEnter(GameState::GameOver), record_run_system)
+            .add_systems(Last, write_save_system.run_if(on_event::<SaveEvent>()));
+    }
+}
+
+#[derive(Event)]
+pub struct SaveEvent;
+
+// none when the file on disk is from a newer build, which this one must not write over
+#[derive(Resource)]
+pub struct SavePath(pub Option<PathBuf>);
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Progress {
+    pub unlocks: BTreeSet<String>,
+    // every mode keeps its own leaderboard
+    pub high_scores: BTreeMap<GameMode, Vec<i32>>,
+    pub runs: Vec<RunRecord>,
+    // the official daily challenge score, by days since the unix epoch
+    pub daily: BTreeMap<u32, i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunRecord {
+    pub score: i32,
+    pub kills: usize,
+    pub finished_at: u64, // unix seconds
+    #[serde(default)]
+    pub aim_assist: bool,
+    #[serde(default)]
+    pub mode: GameMode,
+    // days since the unix epoch, for daily challenge runs
+    #[serde(default)]
+    pub daily: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SaveFile {
+    version: u32,
+    settings: Settings,
+    progress: Progress,
+}
+
+// version 1 kept a single high score table, which became classic's
+#[derive(Deserialize)]
+struct SaveFileV1 {
+    settings: Settings,
+    progress: ProgressV1,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProgressV1 {
+    unlocks: BTreeSet<String>,
+    high_scores: Vec<i32>,
+    runs: Vec<RunRecord>,
+}
+
+// only used to peek at the version before picking a schema to parse with
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+enum SaveError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnknownVersion(u32),
+    // written by a newer build than this one
+    NewerVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "io error: {}", err),
+            SaveError::Parse(err) => write!(f, "parse error: {}", err),
+            SaveError::Serialize(err) => write!(f, "serialize error: {}", err),
+            SaveError::UnknownVersion(version) => write!(f, "unknown save version {}", version),
+            SaveError::NewerVersion(version) => {
+                write!(f, "save version {} is newer than this build", version)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        SaveError::Parse(err)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(err: ron::Error) -> Self {
+        SaveError::Serialize(err)
+    }
+}
+
+impl From<SaveFileV1> for SaveFile {
+    fn from(save: SaveFileV1) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            settings: save.settings,
+            progress: Progress {
+                unlocks: save.progress.unlocks,
+                high_scores: BTreeMap::from([(GameMode::Classic, save.progress.high_scores)]),
+                runs: save.progress.runs,
+                daily: BTreeMap::new(),
+            },
+        }
+    }
+}
+
+impl Progress {
+    pub fn high_scores(&self, mode: GameMode) -> &[i32] {
+        self.high_scores.get(&mode).map_or(&[], Vec::as_slice)
+    }
+
+    // unranked runs only go into the history
+    pub fn record_run(&mut self, run: RunRecord, ranked: bool) {
+        if ranked {
+            let high_scores = self.high_scores.entry(run.mode).or_default();
+            high_scores.push(run.score);
+            high_scores.sort_unstable_by(|a, b| b.cmp(a));
+            high_scores.truncate(HIGH_SCORE_LIMIT);
+        }
+
+        self.runs.push(run);
+        if self.runs.len() > RUN_HISTORY_LIMIT {
+            let overflow = self.runs.len() - RUN_HISTORY_LIMIT;
+            self.runs.drain(..overflow);
+        }
+    }
+}
+
+fn record_run_system(
+    score: Res<Score>,
+    kill_count: Res<KillCount>,
+    aim_assist_used: Res<AimAssistUsed>,
+    mode: Res<GameMode>,
+    daily_run: Res<DailyRun>,
+    mut progress: ResMut<Progress>,
+    mut save_event: EventWriter<SaveEvent>,
+) {
+    let day = daily_run.challenge.as_ref().map(|challenge| challenge.day);
+    let ranked = match day {
+        Some(day) if daily_run.official => {
+            progress.daily.insert(day, score.0);
+            true
+        }
+        Some(_) => false,
+        None => mode.ranked(),
+    };
+    progress.record_run(
+        RunRecord {
+            score: score.0,
+            kills: kill_count.0,
+            finished_at: unix_now(),
+            aim_assist: aim_assist_used.0,
+            mode: *mode,
+            daily: day,
+        },
+        ranked,
+    );
+    save_event.send(SaveEvent);
+}
+
+fn write_save_system(
+    settings: Res<Settings>,
+    progress: Res<Progress>,
+    path: Res<SavePath>,
+    mut save_event: EventReader<SaveEvent>,
+) {
+    save_event.clear();
+    let Some(path) = &path.0 else {
+        return;
+    };
+    if let Err(err) = write_save(path, &settings, &progress) {
+        error!("failed to write save to {}: {}", path.display(), err);
+    }
+}
+
+fn write_save(path: &Path, settings: &Settings, progress: &Progress) -> Result<(), SaveError> {
+    #[derive(Serialize)]
+    struct SaveFileRef<'a> {
+        version: u32,
+        settings: &'a Settings,
+        progress: &'a Progress,
+    }
+
+    let text = ron::ser::to_string_pretty(
+        &SaveFileRef {
+            version: SAVE_VERSION,
+            settings,
+            progress,
+        },
+        ron::ser::PrettyConfig::default(),
+    )?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // write next to the real file and rename over it so a crash mid-write
+    // never leaves a half written save behind
+    let tmp = path.with_extension("ron.tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+    }
+    if path.exists() {
+        fs::copy(path, path.with_extension("ron.bak"))?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// the save to start with, and whether it's safe to write over the file later
+fn load_save(path: &Path) -> (SaveFile, bool) {
+    match read_save(path) {
+        Ok(Some(save)) => return (save, true),
+        Ok(None) => return (SaveFile::default(), true),
+        Err(err @ SaveError::NewerVersion(_)) => {
+            // a newer build's progress is still good, leave it be and play without saving
+            warn!("not touching the save at {}: {}", path.display(), err);
+            return (SaveFile::default(), false);
+        }
+        Err(err) => {
+            warn!("save at {} is unreadable: {}", path.display(), err);
+            let corrupt = path.with_extension("ron.corrupt");
+            if let Err(err) = fs::rename(path, &corrupt) {
+                warn!("could not move aside corrupt save: {}", err);
+            }
+        }
+    }
+
+    let backup = path.with_extension("ron.bak");
+    match read_save(&backup) {
+        Ok(Some(save)) => {
+            info!("recovered save from {}", backup.display());
+            (save, true)
+        }
+        _ => (SaveFile::default(), true),
+    }
+}
+
+fn read_save(path: &Path) -> Result<Option<SaveFile>, SaveError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    parse_save(&text).map(Some)
+}
+
+fn parse_save(text: &str) -> Result<SaveFile, SaveError> {
+    let header: SaveHeader = ron::from_str(text)?;
+    match header.version {
+        SAVE_VERSION => Ok(ron::from_str(text)?),
+        version if version > SAVE_VERSION => Err(SaveError::NewerVersion(version)),
+        version => migrate(version, text),
+    }
+}
+
+// lifts a save from an older layout up to the current one; each retired
+// layout keeps a `SaveFileV<n>` that converts into the layout after it
+fn migrate(version: u32, text: &str) -> Result<SaveFile, SaveError> {
+    match version {
+        1 => Ok(ron::from_str::<SaveFileV1>(text)?.into()),
+        version => Err(SaveError::UnknownVersion(version)),
+    }
+}
+
+fn save_path() -> PathBuf {
+    data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(SAVE_FILE)
+}
+
+fn data_dir() -> Option<PathBuf> {
+    let env_dir = |key: &str| std::env::var_os(key).map(PathBuf::from);
+
+    #[cfg(target_os = "windows")]
+    let base = env_dir("APPDATA");
+    #[cfg(target_os = "macos")]
+    let base = env_dir("HOME").map(|home| home.join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base =
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")));
+
+    base.map(|dir| dir.join("handgun-heroes"))
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_SAVE: &str = r#"(
+        version: 1,
+        settings: (fov: 60.0),
+        progress: (
+            unlocks: ["rail"],
+            high_scores: [900, 400],
+            runs: [(score: 900, kills: 9, finished_at: 1700000000)],
+        ),
+    )"#;
+
+    // a fresh directory per test so they can run side by side
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("handgun-heroes-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn progress_with_score(score: i32) -> Progress {
+        let mut progress = Progress::default();
+        progress.record_run(
+            RunRecord {
+                score,
+                kills: 1,
+                finished_at: 0,
+                aim_assist: false,
+                mode: GameMode::TimeAttack60,
+                daily: None,
+            },
+            true,
+        );
+        progress
+    }
+
+    #[test]
+    fn migrates_v1_scores_to_classic() {
+        let save = parse_save(V1_SAVE).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.settings.fov, 60.);
+        assert!(save.progress.unlocks.contains("rail"));
+        assert_eq!(save.progress.high_scores(GameMode::Classic), [900, 400]);
+        assert!(save.progress.high_scores(GameMode::TimeAttack60).is_empty());
+        assert_eq!(save.progress.runs.len(), 1);
+        assert_eq!(save.progress.runs[0].mode, GameMode::Classic);
+        assert_eq!(save.progress.runs[0].daily, None);
+        assert!(save.progress.daily.is_empty());
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let dir = scratch_dir("round-trip");
+        let path = dir.join(SAVE_FILE);
+        write_save(&path, &Settings::default(), &progress_with_score(1200)).unwrap();
+
+        let save = read_save(&path).unwrap().unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.settings, Settings::default());
+        assert_eq!(save.progress.high_scores(GameMode::TimeAttack60), [1200]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_garbage() {
+        assert!(matches!(
+            parse_save("(version: 0)"),
+            Err(SaveError::UnknownVersion(0))
+        ));
+        assert!(matches!(
+            parse_save("definitely not a save"),
+            Err(SaveError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn missing_save_starts_fresh() {
+        let dir = scratch_dir("missing");
+        let (save, writable) = load_save(&dir.join(SAVE_FILE));
+        assert!(save.progress.runs.is_empty());
+        assert!(writable);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn newer_save_is_left_alone() {
+        let dir = scratch_dir("newer");
+        let path = dir.join(SAVE_FILE);
+        let newer = "(version: 99, progress: (high_scores: [5000]))";
+        fs::write(&path, newer).unwrap();
+
+        let (save, writable) = load_save(&path);
+        assert!(save.progress.high_scores.is_empty());
+        assert!(!writable);
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        assert!(!path.with_extension("ron.corrupt").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_save_is_moved_aside_and_backup_loaded() {
+        let dir = scratch_dir("corrupt");
+        let path = dir.join(SAVE_FILE);
+        // the second write leaves the first behind as the backup
+        write_save(&path, &Settings::default(), &progress_with_score(500)).unwrap();
+        write_save(&path, &Settings::default(), &progress_with_score(700)).unwrap();
+        fs::write(&path, "(version: 2, settings: (fov: ").unwrap();
+
+        let (save, writable) = load_save(&path);
+        assert_eq!(save.progress.high_scores(GameMode::TimeAttack60), [500]);
+        assert!(writable);
+        let corrupt = fs::read_to_string(path.with_extension("ron.corrupt")).unwrap();
+        assert_eq!(corrupt, "(version: 2, settings: (fov: ");
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use crate::calibration::TrackerCalibration;
+use crate::gesture::GestureConfig;
+use crate::input::InputFilters;
+use crate::player::CamMarker;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (apply_camera_settings_system, apply_window_settings_system)
+                .run_if(resource_changed::<Settings>),
+        );
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub mouse_sensitivity: f32,
+    pub fov: f32, // degrees
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ui_volume: f32,
+    pub fullscreen: bool,
+    // scales shake, vignette and hit-stop, 0 turns them all off
+    pub damage_feedback: f32,
+    // drops camera shake and hit-stop but keeps the vignette
+    pub reduced_motion: bool,
+    pub show_radar: bool,
+    // how hard shots bend toward enemies near the crosshair, 0 turns assist off
+    pub aim_assist: f32,
+    pub aim_assist_radius: f32, // px
+    pub input_filters: InputFilters,
+    // calibrations are kept per camera setup, this picks the one in use
+    pub tracker_profile: String,
+    pub tracker_calibrations: BTreeMap<String, TrackerCalibration>,
+    pub gestures: GestureConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.0005,
+            fov: 45.,
+            fog_start: 15.,
+            fog_end: 100.,
+            master_volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            ui_volume: 1.,
+            fullscreen: false,
+            damage_feedback: 1.,
+            reduced_motion: false,
+            show_radar: true,
+            aim_assist: 0.,
+            aim_assist_radius: 80.,
+            input_filters: InputFilters::default(),
+            tracker_profile: "default".to_string(),
+            tracker_calibrations: BTreeMap::new(),
+            gestures: GestureConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn tracker_calibration(&self) -> Option<&TrackerCalibration> {
+        self.tracker_calibrations.get(&self.tracker_profile)
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+}
+
+fn apply_camera_settings_system(
+    settings: Res<Settings>,
+    mut cam: Query<(&mut Projection, &mut FogSettings), With<CamMarker>>,
+) {
+    for (mut projection, mut fog) in cam.iter_mut() {
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.fov.to_radians();
+        }
+        fog.falloff = FogFalloff::Linear {
+            start: settings.fog_start,
+            end: settings.fog_end,
+        };
+    }
+}
+
+fn apply_window_settings_system(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let mode = settings.window_mode();
+    for mut window in windows.iter_mut() {
+        if window.mode != mode {
+            window.mode = mode;
+        }
+    }
+}
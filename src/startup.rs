@@ -1,33 +1,64 @@
+use crate::aim::AimPlugin;
+use crate::audio::AudioManagerPlugin;
+use crate::calibration::CalibrationPlugin;
+use crate::coop::CoopPlugin;
+use crate::effects::EffectsPlugin;
 use crate::enemy::*;
+use crate::feedback::DamageFeedbackPlugin;
+use crate::gesture::GesturePlugin;
 use crate::globals::*;
 use crate::hud::*;
+use crate::indicators::ThreatIndicatorPlugin;
+use crate::input::PointerInputPlugin;
+use crate::menu::MenuPlugin;
+use crate::mode::GameModePlugin;
 use crate::player::*;
+use crate::powerup::PowerUpPlugin;
+use crate::practice::PracticePlugin;
+use crate::save::SavePlugin;
+use crate::settings::SettingsPlugin;
+use crate::synthetic::SyntheticTrackerPlugin;
 use bevy::input::common_conditions::input_just_pressed;
 use bevy::prelude::*;
-use bevy::window::{CursorGrabMode, PrimaryWindow};
 
 pub struct GameStartUp;
 
 impl Plugin for GameStartUp {
     fn build(&self, app: &mut App) {
-        app.add_plugins(HudPlugin)
+        app.add_plugins(SavePlugin)
+            .add_plugins(SettingsPlugin)
+            .add_plugins(AudioManagerPlugin)
+            .add_plugins(MenuPlugin)
+            .add_plugins(PointerInputPlugin)
+            .add_plugins(SyntheticTrackerPlugin)
+            .add_plugins(GesturePlugin)
+            .add_plugins(CoopPlugin)
+            .add_plugins(AimPlugin)
+            .add_plugins(CalibrationPlugin)
+            .add_plugins(EffectsPlugin)
+            .add_plugins(DamageFeedbackPlugin)
+            .add_plugins(HudPlugin)
+            .add_plugins(ThreatIndicatorPlugin)
             .add_plugins(PlayerPlugin)
             .add_plugins(EnemyPlugin)
+            .add_plugins(PowerUpPlugin)
+            .add_plugins(GameModePlugin)
+            .add_plugins(PracticePlugin)
             .add_plugins(Global)
-            .add_systems(Startup, (init_world_system, init_bgm))
+            .add_systems(Startup, init_world_system)
             .add_systems(Update, debug_system)
             .add_systems(
                 Update,
                 reset_system.run_if(input_just_pressed(KeyCode::KeyR)),
             );
 
-        app.insert_state(GameState::InGame);
+        app.insert_state(GameState::MainMenu)
+            .add_sub_state::<PauseState>();
     }
 }
 
 fn init_world_system(
     mut commands: Commands,
-    mut windows: Query<&mut Window, With<PrimaryWindow>>,
     mut mesh: ResMut<Assets<Mesh>>,
     mut material: ResMut<Assets<StandardMaterial>>,
 ) {
@@ -45,17 +76,6 @@ fn init_world_system(
         ..default()
     };
     commands.spawn(sphere);
-
-    let mut windows = windows.single_mut();
-    windows.cursor.grab_mode = CursorGrabMode::Locked;
-    windows.cursor.visible = false;
-}
-
-fn init_bgm(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(AudioBundle {
-        source: asset_server.load("zenith.ogg"),
-        settings: PlaybackSettings::LOOP,
-    });
 }
 
 fn debug_system(input: Res<ButtonInput<MouseButton>>, cam_pos: Query<&Transform, With<CamMarker>>) {
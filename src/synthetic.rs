@@ -0,0 +1,380 @@
+use crate::input::{update_pointer_system, TrackerMessage};
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs;
+
+// set to `procedural`, `procedural:<seed>` or the path of a recording to
+// stand in for the webcam tracker
+const SYNTHETIC_TRACKER_ENV: &str = "SYNTHETIC_TRACKER";
+// the webcam tracker runs at roughly this rate, so the filters see the same cadence
+const SAMPLE_RATE: f32 = 30.;
+const AIM_JITTER: f32 = 0.004;
+const PINCH_OPEN: f32 = 0.12;
+const PINCH_CLOSED: f32 = 0.02;
+const PINCH_EVERY: f32 = 1.5;
+const PINCH_HOLD: f32 = 0.15;
+const FIST_EVERY: f32 = 4.;
+// chance per sample that the fist reading flickers, like the real classifier does
+const FIST_FLICKER: f64 = 0.05;
+
+pub struct SyntheticTrackerPlugin;
+
+impl Plugin for SyntheticTrackerPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(tracker) = SyntheticTracker::from_env() else {
+            return;
+        };
+        app.insert_resource(tracker).add_systems(
+            PreUpdate,
+            synthetic_tracker_system.before(update_pointer_system),
+        );
+    }
+}
+
+// plays a tracker stream back through the same protocol the real one speaks
+#[derive(Resource)]
+pub enum SyntheticTracker {
+    Procedural {
+        rng: Box<StdRng>,
+        elapsed: f32,
+        next_sample: f32,
+    },
+    // (seconds since start, protocol line), looped
+    Recording {
+        lines: Vec<(f32, String)>,
+        elapsed: f32,
+        next_line: usize,
+    },
+}
+
+impl SyntheticTracker {
+    fn from_env() -> Option<Self> {
+        let source = std::env::var(SYNTHETIC_TRACKER_ENV).ok()?;
+        if let Some(seed) = source.strip_prefix("procedural") {
+            let seed = seed.trim_start_matches(':').parse().unwrap_or(0);
+            info!("playing a procedural tracker stream, seed {seed}");
+            return Some(Self::procedural(seed));
+        }
+        match fs::read_to_string(&source) {
+            Ok(text) => {
+                info!("playing back tracker recording {source}");
+                Some(Self::recording(&text))
+            }
+            Err(err) => {
+                warn!("can't read tracker recording {source}: {err}");
+                None
+            }
+        }
+    }
+
+    pub fn procedural(seed: u64) -> Self {
+        SyntheticTracker::Procedural {
+            rng: Box::new(StdRng::seed_from_u64(seed)),
+            elapsed: 0.,
+            next_sample: 0.,
+        }
+    }
+
+    // one `<seconds> <message>` per line, blank lines and `#` comments skipped
+    pub fn recording(text: &str) -> Self {
+        let mut lines: Vec<(f32, String)> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (at, message) = line.split_once(char::is_whitespace)?;
+                Some((at.parse().ok()?, message.trim().to_string()))
+            })
+            .collect();
+        lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+        SyntheticTracker::Recording {
+            lines,
+            elapsed: 0.,
+            next_line: 0,
+        }
+    }
+
+    // every protocol line due within the next `dt` seconds
+    pub fn advance(&mut self, dt: f32) -> Vec<String> {
+        let mut out = Vec::new();
+        match self {
+            SyntheticTracker::Procedural {
+                rng,
+                elapsed,
+                next_sample,
+            } => {
+                *elapsed += dt;
+                while *next_sample <= *elapsed {
+                    out.extend(procedural_sample(rng, *next_sample));
+                    *next_sample += 1. / SAMPLE_RATE;
+                }
+            }
+            SyntheticTracker::Recording {
+                lines,
+                elapsed,
+                next_line,
+            } => {
+                let Some(duration) = lines.last().map(|(at, _)| *at) else {
+                    return out;
+                };
+                *elapsed += dt;
+                while let Some((at, line)) = lines.get(*next_line) {
+                    if *at > *elapsed {
+                        break;
+                    }
+                    out.push(line.clone());
+                    *next_line += 1;
+                }
+                if *next_line >= lines.len() {
+                    *elapsed -= duration;
+                    *next_line = 0;
+                }
+            }
+        }
+        out
+    }
+}
+
+// a slow figure eight with jitter, a pinch every so often and a fist that
+// opens and closes with the odd flicker
+fn procedural_sample(rng: &mut StdRng, t: f32) -> [String; 3] {
+    let jitter = Vec2::new(
+        rng.gen_range(-AIM_JITTER..=AIM_JITTER),
+        rng.gen_range(-AIM_JITTER..=AIM_JITTER),
+    );
+    let aim = Vec2::new(
+        0.5 + 0.35 * (0.7 * t).sin(),
+        0.5 + 0.3 * (1.1 * t + 0.5).sin(),
+    ) + jitter;
+
+    let pinching = t % PINCH_EVERY < PINCH_HOLD;
+    let pinch = if pinching { PINCH_CLOSED } else { PINCH_OPEN } + rng.gen_range(-0.01..=0.01);
+
+    let closed = (t / FIST_EVERY) as u32 % 2 == 1;
+    let closed = closed != rng.gen_bool(FIST_FLICKER);
+
+    [
+        format!("aim {:.4} {:.4}", aim.x, aim.y),
+        format!("pinch {:.4}", pinch),
+        format!("fist {}", closed as u8),
+    ]
+}
+
+fn synthetic_tracker_system(
+    mut tracker: ResMut<SyntheticTracker>,
+    mut messages: EventWriter<TrackerMessage>,
+    time: Res<Time<Real>>,
+) {
+    let lines = tracker.advance(time.delta_seconds());
+    messages.send_batch(lines.iter().filter_map(|line| TrackerMessage::parse(line)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calibration::TrackerCalibration;
+    use crate::gesture::{gesture_system, GestureAction, GestureConfig, Gestures};
+    use crate::globals::Kulay;
+    use crate::input::{FilterState, InputFilters};
+    use crate::settings::Settings;
+    use bevy::utils::{Duration, Instant};
+
+    const WINDOW: Vec2 = Vec2::new(1280., 720.);
+
+    // (seconds, message) for every sample in the first `secs` of the stream
+    fn stream(seed: u64, secs: f32) -> Vec<(f32, TrackerMessage)> {
+        let lines = SyntheticTracker::procedural(seed).advance(secs);
+        // every sample is an aim, a pinch and a fist line, in that order
+        lines
+            .chunks(3)
+            .enumerate()
+            .flat_map(|(index, sample)| {
+                let t = index as f32 / SAMPLE_RATE;
+                sample
+                    .iter()
+                    .map(move |line| (t, TrackerMessage::parse(line).unwrap()))
+            })
+            .collect()
+    }
+
+    fn aims(seed: u64, secs: f32) -> Vec<(f32, Vec2)> {
+        stream(seed, secs)
+            .into_iter()
+            .filter_map(|(t, message)| match message {
+                TrackerMessage::Aim(raw) => Some((t, raw)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // runs `gesture_system` alone, one frame per (seconds, messages) step, and
+    // collects what it sent with the time it was sent
+    fn gesture_actions(steps: Vec<(f32, Vec<TrackerMessage>)>) -> Vec<(f32, GestureAction)> {
+        let start = Instant::now();
+        let mut app = App::new();
+        app.add_event::<TrackerMessage>()
+            .add_event::<GestureAction>()
+            .insert_resource(Settings::default())
+            .insert_resource(Gestures::default())
+            .insert_resource(Time::<Real>::new(start))
+            .add_systems(Update, gesture_system);
+        let set_time = |app: &mut App, t: f32| {
+            let at = start + Duration::from_secs_f32(t);
+            app.world_mut()
+                .resource_mut::<Time<Real>>()
+                .update_with_instant(at);
+        };
+        set_time(&mut app, 0.);
+
+        let mut actions = Vec::new();
+        for (t, messages) in steps {
+            set_time(&mut app, t);
+            app.world_mut().send_event_batch(messages);
+            app.update();
+            let mut sent = app.world_mut().resource_mut::<Events<GestureAction>>();
+            actions.extend(sent.drain().map(|action| (t, action)));
+        }
+        actions
+    }
+
+    fn mean_second_difference(points: &[Vec2]) -> f32 {
+        let total: f32 = points
+            .windows(3)
+            .map(|w| (w[2] - 2. * w[1] + w[0]).length())
+            .sum();
+        total / (points.len() - 2) as f32
+    }
+
+    #[test]
+    fn procedural_stream_is_reproducible() {
+        let mut a = SyntheticTracker::procedural(7);
+        let mut b = SyntheticTracker::procedural(7);
+        for dt in [0.016, 0.05, 0.2, 0.033, 1.] {
+            assert_eq!(a.advance(dt), b.advance(dt));
+        }
+        assert_ne!(
+            SyntheticTracker::procedural(7).advance(1.),
+            SyntheticTracker::procedural(8).advance(1.)
+        );
+    }
+
+    #[test]
+    fn filter_smooths_jitter_and_keeps_up() {
+        let config = &InputFilters::default().tracker;
+        let mut filter = FilterState::default();
+        let samples = aims(3, 10.);
+        let raw: Vec<Vec2> = samples.iter().map(|(_, raw)| *raw * WINDOW).collect();
+        let filtered: Vec<Vec2> = samples
+            .iter()
+            .zip(&raw)
+            .map(|((t, _), raw)| filter.apply(config, *raw, *t))
+            .collect();
+
+        // jitter shows up as sample to sample wobble, the figure eight barely bends
+        assert!(mean_second_difference(&filtered) < mean_second_difference(&raw) * 0.5);
+        let lag: f32 = filtered
+            .iter()
+            .zip(&raw)
+            .map(|(filtered, raw)| filtered.distance(*raw))
+            .sum::<f32>()
+            / raw.len() as f32;
+        assert!(
+            lag < 20.,
+            "filtered aim trails the raw aim by {lag}px on average"
+        );
+    }
+
+    #[test]
+    fn calibration_recovers_an_affine_map() {
+        let truth = TrackerCalibration {
+            x: [1.2, -0.1, 0.05],
+            y: [0.05, 0.9, -0.02],
+        };
+        let pairs: Vec<(Vec2, Vec2)> = aims(11, 5.)
+            .into_iter()
+            .map(|(_, raw)| (raw, truth.apply(raw)))
+            .collect();
+
+        let fitted = TrackerCalibration::fit(&pairs).unwrap();
+        for (fitted, truth) in fitted
+            .x
+            .iter()
+            .chain(&fitted.y)
+            .zip(truth.x.iter().chain(&truth.y))
+        {
+            assert!((fitted - truth).abs() < 1e-3, "{fitted} != {truth}");
+        }
+
+        // a single spot says nothing about scale or rotation
+        let still = vec![(Vec2::splat(0.5), Vec2::splat(0.5)); 5];
+        assert!(TrackerCalibration::fit(&still).is_none());
+    }
+
+    #[test]
+    fn gestures_settle_into_clean_edges() {
+        // every sample is an aim, a pinch and a fist line at the same time
+        let steps = stream(5, 10.)
+            .chunks(3)
+            .map(|sample| {
+                let messages = sample.iter().map(|(_, message)| message.clone());
+                (sample[0].0, messages.collect())
+            })
+            .collect();
+        let actions: Vec<GestureAction> = gesture_actions(steps)
+            .into_iter()
+            .map(|(_, action)| action)
+            .collect();
+
+        // a pinch every PINCH_EVERY seconds from the start, each let go again
+        let pinches = (10. / PINCH_EVERY).ceil() as usize;
+        let triggers: Vec<GestureAction> = actions
+            .iter()
+            .copied()
+            .filter(|action| matches!(action, GestureAction::Fire | GestureAction::CeaseFire))
+            .collect();
+        assert_eq!(
+            triggers,
+            [GestureAction::Fire, GestureAction::CeaseFire].repeat(pinches)
+        );
+        // the fist closes after FIST_EVERY seconds and opens again after twice that,
+        // held long enough in between to pick yellow; the flicker never lasts long
+        // enough to count
+        let colors: Vec<Kulay> = actions
+            .iter()
+            .filter_map(|action| match action {
+                GestureAction::SelectWeapon(color) => Some(*color),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(colors, [Kulay::Pula, Kulay::Dilaw, Kulay::Asul]);
+    }
+
+    #[test]
+    fn lost_hand_lets_go_of_the_pinch() {
+        let timeout = GestureConfig::default().hand_timeout;
+        let last_reading = 0.3;
+        // a held pinch, then the tracker goes quiet
+        let steps = (0..60)
+            .map(|frame| {
+                let t = frame as f32 / SAMPLE_RATE;
+                let messages = if t <= last_reading {
+                    vec![TrackerMessage::Pinch(PINCH_CLOSED)]
+                } else {
+                    Vec::new()
+                };
+                (t, messages)
+            })
+            .collect();
+        let actions = gesture_actions(steps);
+
+        assert_eq!(actions.len(), 2, "{actions:?}");
+        assert_eq!(actions[0].1, GestureAction::Fire);
+        assert_eq!(actions[1].1, GestureAction::CeaseFire);
+        let released = actions[1].0 - last_reading;
+        assert!(
+            (timeout..timeout + 2. / SAMPLE_RATE).contains(&released),
+            "let go {released}s after the last reading"
+        );
+    }
+}